@@ -0,0 +1,93 @@
+//! Measures lexlucid's throughput once its regular expressions have been compiled.
+//!
+//! The `lex` group reports throughput in bytes, and the `lex-tokens` group in tokens.
+//!
+//! lexlucid compiles each of its regular expressions the first time it's needed, so each benchmark
+//! lexes its input once before it starts timing.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use lexeywan::{lex, Edition};
+
+/// A block comment nested 50 levels deep, with some text at each level.
+const NESTED_COMMENT: &str = concat!(
+    "/* a /* a /* a /* a /* a /* a /* a /* a /* a /* a ",
+    "/* a /* a /* a /* a /* a /* a /* a /* a /* a /* a ",
+    "/* a /* a /* a /* a /* a /* a /* a /* a /* a /* a ",
+    "/* a /* a /* a /* a /* a /* a /* a /* a /* a /* a ",
+    "/* a /* a /* a /* a /* a /* a /* a /* a /* a /* a ",
+    "*/ b */ b */ b */ b */ b */ b */ b */ b */ b */ b ",
+    "*/ b */ b */ b */ b */ b */ b */ b */ b */ b */ b ",
+    "*/ b */ b */ b */ b */ b */ b */ b */ b */ b */ b ",
+    "*/ b */ b */ b */ b */ b */ b */ b */ b */ b */ b ",
+    "*/ b */ b */ b */ b */ b */ b */ b */ b */ b */ b ",
+);
+
+const INPUTS: &[(&str, &str)] = &[
+    ("identifiers", "fn main() { let x = foo::bar(y, z); }"),
+    (
+        "literals",
+        r##"'a' b'b' "string" b"bytes" c"cstr" r#"raw"# 1_000u32 0x7f 1.5e10f64"##,
+    ),
+    (
+        "comments",
+        "/// doc\n// line\n/* block /* nested */ */\n//! inner\nx",
+    ),
+    (
+        "punctuation",
+        "a += b << 2 >>= c && d || !e ..= f => g :: h",
+    ),
+    ("nested-comment", NESTED_COMMENT),
+    (
+        "source-file",
+        include_str!("../src/lexlucid/pretokenisation.rs"),
+    ),
+];
+
+/// Returns the inputs from `INPUTS`, and inputs which are too long to write out.
+fn inputs() -> Vec<(&'static str, String)> {
+    let mut inputs: Vec<_> = INPUTS
+        .iter()
+        .map(|(name, input)| (*name, input.to_string()))
+        .collect();
+    inputs.push((
+        "long-raw-string",
+        format!(
+            "r##\"{}\"##",
+            "raw text with \"quotes\"# and # hashes\n".repeat(50)
+        ),
+    ));
+    inputs.push(("many-punctuation", "+ -= * / ; , . :: -> => # ".repeat(50)));
+    inputs
+}
+
+fn lexing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lex");
+    for (name, input) in inputs() {
+        for edition in [Edition::E2015, Edition::E2021, Edition::E2024] {
+            lex(&input, edition).expect("benchmark input should be accepted");
+            group.throughput(Throughput::Bytes(input.len() as u64));
+            group.bench_with_input(
+                BenchmarkId::new(name, format!("{edition:?}")),
+                &input,
+                |b, input| b.iter(|| lex(black_box(input), edition)),
+            );
+        }
+    }
+    group.finish();
+}
+
+fn lexing_tokens(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lex-tokens");
+    for (name, input) in inputs() {
+        let tokens = lex(&input, Edition::E2021).expect("benchmark input should be accepted");
+        group.throughput(Throughput::Elements(tokens.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &input, |b, input| {
+            b.iter(|| lex(black_box(input), Edition::E2021))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, lexing, lexing_tokens);
+criterion_main!(benches);
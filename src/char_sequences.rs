@@ -21,6 +21,27 @@ impl Charseq {
         Charseq(chars)
     }
 
+    /// Returns a new `Charseq` representing the characters with the specified scalar values.
+    ///
+    /// Returns the first value which isn't a Unicode scalar value (a surrogate, or a value above
+    /// 0x10FFFF) as an error.
+    pub fn from_scalar_values(values: &[u32]) -> Result<Charseq, u32> {
+        values
+            .iter()
+            .map(|&value| char::from_u32(value).ok_or(value))
+            .collect()
+    }
+
+    /// Appends a character to the end of the sequence.
+    pub fn push_char(&mut self, c: char) {
+        self.0.push(c)
+    }
+
+    /// Appends the characters of a string to the end of the sequence.
+    pub fn extend_from_str(&mut self, s: &str) {
+        self.0.extend(s.chars())
+    }
+
     /// Returns the number of characters in the sequence.
     pub fn len(&self) -> usize {
         self.0.len()
@@ -52,6 +73,11 @@ impl Charseq {
     pub fn nfc(&self) -> Self {
         self.iter().copied().nfc().collect()
     }
+
+    /// Returns `true` iff the two sequences are equal after converting both to Normalisation Form C.
+    pub fn nfc_eq(&self, other: &Charseq) -> bool {
+        self.nfc() == other.nfc()
+    }
 }
 
 impl std::fmt::Display for Charseq {
@@ -132,6 +158,7 @@ impl std::fmt::Debug for Charseq {
 }
 
 /// Returns a new `Charseq` containing the characters of `l1` followed by the characters of `l2`.
+#[cfg(feature = "rustc-harness")]
 pub fn concat_charseqs(l1: &Charseq, l2: &Charseq) -> Charseq {
     let mut chars = l1.0.clone();
     chars.extend(l2.iter());
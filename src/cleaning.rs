@@ -3,24 +3,169 @@
 //! See <https://doc.rust-lang.org/nightly/reference/input-format.html> for the behavour we're
 //! imitating.
 
+use std::ops::Range;
+
 use regex::{Regex, RegexBuilder};
 
+use crate::lexlucid::{self, FineTokenData};
+#[cfg(feature = "rustc-harness")]
+use crate::utils::escape_for_display;
+use crate::Edition;
+
+/// Which transformations to make.
+///
+/// Each step can be turned on or off independently (so that it's possible to see which step is
+/// responsible for a difference). The associated constants are the combinations which correspond
+/// to the `--cleaning` presets.
+#[derive(Copy, Clone, PartialEq, Eq, std::fmt::Debug)]
+pub struct CleaningMode {
+    /// Remove a byte order mark from the start of the input.
+    pub strip_bom: bool,
+    /// Convert CRLF to LF.
+    pub normalise_crlf: bool,
+    /// Remove a shebang line.
+    pub strip_shebang: bool,
+    /// Remove a frontmatter block.
+    pub strip_frontmatter: bool,
+}
+
+impl CleaningMode {
+    /// Make no transformations.
+    #[cfg(any(test, feature = "rustc-harness"))]
+    pub const NO_CLEANING: Self = Self {
+        strip_bom: false,
+        normalise_crlf: false,
+        strip_shebang: false,
+        strip_frontmatter: false,
+    };
+
+    /// Remove a byte order mark, convert CRLF to LF, and remove a shebang line.
+    ///
+    /// This is what rustc does.
+    pub const CLEAN_SHEBANG: Self = Self {
+        strip_bom: true,
+        normalise_crlf: true,
+        strip_shebang: true,
+        strip_frontmatter: false,
+    };
+
+    /// As `CLEAN_SHEBANG`, and also remove a frontmatter block.
+    ///
+    /// This is the behaviour proposed for cargo-script by RFC 3503. The version of rustc we compare
+    /// against doesn't support frontmatter.
+    #[cfg(feature = "rustc-harness")]
+    pub const CLEAN_SHEBANG_AND_FRONTMATTER: Self = Self {
+        strip_frontmatter: true,
+        ..Self::CLEAN_SHEBANG
+    };
+}
+
+/// What cleaning removed from an input.
+#[derive(Default)]
+pub struct CleaningReport {
+    /// Whether a byte order mark was removed from the start of the input.
+    pub removed_bom: bool,
+    /// How many CRLF sequences were converted to LF.
+    pub converted_crlfs: usize,
+    /// The shebang line which was removed (including its newline), if there was one.
+    pub removed_shebang: Option<String>,
+    /// The byte range of the frontmatter which was removed, if there was any.
+    ///
+    /// The offsets are into the input as it was just before the frontmatter was removed (that is,
+    /// after the other transformations). Anything before the frontmatter is left in place, so this
+    /// is also where the frontmatter would have been in the cleaned input.
+    pub removed_frontmatter: Option<Range<usize>>,
+}
+
+#[cfg(feature = "rustc-harness")]
+impl CleaningReport {
+    /// Describes what was removed, one string per line.
+    ///
+    /// Returns an empty list if cleaning didn't remove anything.
+    pub fn describe(&self) -> Vec<String> {
+        let mut description = Vec::new();
+        if self.removed_bom {
+            description.push("removed byte order mark".into());
+        }
+        if self.converted_crlfs != 0 {
+            description.push(format!("converted {} CRLF to LF", self.converted_crlfs));
+        }
+        if let Some(shebang) = &self.removed_shebang {
+            description.push(format!("removed shebang «{}»", escape_for_display(shebang)));
+        }
+        if let Some(range) = &self.removed_frontmatter {
+            description.push(format!(
+                "removed frontmatter (bytes {}..{})",
+                range.start, range.end
+            ));
+        }
+        description
+    }
+}
+
 /// Apply the transformations we make to input text before tokenisation.
+///
+/// This uses [`CleaningMode::CLEAN_SHEBANG`].
 pub fn clean(input: &str) -> String {
+    let (cleaned, _) = clean_with_report(input, CleaningMode::CLEAN_SHEBANG)
+        .expect("cleaning without frontmatter should never fail");
+    cleaned
+}
+
+/// Apply the transformations we make to input text before tokenisation, and report what was
+/// removed.
+///
+/// Returns an error message if the input has an unterminated frontmatter block (this can only
+/// happen if `mode` has `strip_frontmatter` set).
+pub fn clean_with_report(
+    input: &str,
+    mode: CleaningMode,
+) -> Result<(String, CleaningReport), String> {
+    let mut report = CleaningReport::default();
     let mut rest = input;
 
-    // Remove BOM
-    if rest.starts_with('\u{feff}') {
-        rest = &rest[3..];
+    // Remove BOM (only at the very start; a BOM anywhere else is left for the lexer)
+    if mode.strip_bom {
+        if let Some(after_bom) = rest.strip_prefix('\u{feff}') {
+            rest = after_bom;
+            report.removed_bom = true;
+        }
     }
 
     // CRLF -> LF
-    let mut cleaned = rest.replace("\r\n", "\n");
+    let mut cleaned = if mode.normalise_crlf {
+        let (cleaned, converted_crlfs) = normalise_crlf(rest);
+        report.converted_crlfs = converted_crlfs;
+        cleaned
+    } else {
+        rest.to_owned()
+    };
 
     // Remove shebang
-    clean_shebang(&mut cleaned);
+    if mode.strip_shebang {
+        report.removed_shebang = clean_shebang(&mut cleaned);
+    }
 
-    cleaned
+    // Remove frontmatter
+    if mode.strip_frontmatter {
+        report.removed_frontmatter = clean_frontmatter(&mut cleaned)?;
+    }
+
+    Ok((cleaned, report))
+}
+
+/// Converts each CRLF sequence to LF, as rustc's `SourceMap` does.
+///
+/// A CR which isn't immediately followed by LF is left in place (the lexer rejects it if it appears
+/// in a literal or doc-comment).
+///
+/// Returns the converted text and the number of CRLF sequences converted.
+fn normalise_crlf(input: &str) -> (String, usize) {
+    let count = input.matches("\r\n").count();
+    if count == 0 {
+        return (input.to_owned(), 0);
+    }
+    (input.replace("\r\n", "\n"), count)
 }
 
 fn mkre(s: &str) -> Regex {
@@ -37,30 +182,76 @@ macro_rules! make_regex {
     }};
 }
 
-/// Approximation to rustc's shebang-cleaning.
+/// Imitation of rustc's shebang-cleaning.
 ///
 /// We're not supposed to remove the first line if it looks like the start of a Rust attribute.
 ///
-/// The implementation below for that exception only accepts whitespace after the `#!`, so
-/// it goes wrong if there's a comment there.
-/// rustc deals with this by running its lexer for long enough to answer this question and throwing
-/// away the result. I suppose we could do something similar.
-fn clean_shebang(input: &mut String) {
+/// Like rustc, we decide that by lexing the input after the `#!` for long enough to find the
+/// first token which isn't whitespace or a non-doc-comment, and checking whether that token is `[`.
+/// If lexing fails before we find such a token, the input isn't treated as an attribute.
+///
+/// Returns the removed line, if there was one.
+fn clean_shebang(input: &mut String) -> Option<String> {
+    let tail = input.strip_prefix("#!")?;
+    // Whitespace and comments are lexed the same way in every edition.
+    if let Some(token) = lexlucid::first_significant_token(tail, Edition::E2021) {
+        if let FineTokenData::Punctuation { mark: '[' } = token.data {
+            return None;
+        }
+    }
     #[rustfmt::skip]
-    let attributelike_re = make_regex!(r##"\A
+    let shebang_re = make_regex!(r##"\A
         \# !
-        [ \p{Pattern_White_Space} ] *
-        \[
+        .*?
+        ( \n | \z )
     "##);
-    if !attributelike_re.is_match(input) {
-        #[rustfmt::skip]
-        let shebang_re = make_regex!(r##"\A
-            \# !
-            .*?
-            ( \n | \z )
-        "##);
-        if let Some(m) = shebang_re.find(input) {
-            input.replace_range(..m.end(), "");
+    let m = shebang_re.find(input)?;
+    let removed = m.as_str().to_owned();
+    input.replace_range(..m.end(), "");
+    Some(removed)
+}
+
+/// Removes a frontmatter block, as proposed for cargo-script by RFC 3503.
+///
+/// The frontmatter may be preceded only by lines containing nothing but whitespace. It begins with
+/// an opening fence: a line consisting of three or more `-` characters, optionally followed by an
+/// infostring. It ends with a closing fence: a line consisting of the same number of `-`
+/// characters, optionally followed by whitespace.
+///
+/// Lines may end with CRLF as well as LF, so that this works whether or not CRLF has been
+/// converted first.
+///
+/// Returns the byte range which was removed, or None if there was no frontmatter.
+///
+/// Returns an error message if there's an opening fence but no closing fence.
+fn clean_frontmatter(input: &mut String) -> Result<Option<Range<usize>>, String> {
+    #[rustfmt::skip]
+    let opening_fence_re = make_regex!(r##"\A
+        (?: [\ \t] * \r? \n ) *
+        (?<fence> -{3,} )
+        [\ \t] *
+        (?: [ _ \p{XID_Start} ] [ \p{XID_Continue} \- . ] * ) ?
+        [\ \t] *
+        (?: \r? \n | \z )
+    "##);
+    let Some(captures) = opening_fence_re.captures(input) else {
+        return Ok(None);
+    };
+    let fence = captures.name("fence").unwrap().as_str().to_owned();
+    let start = captures.name("fence").unwrap().start();
+    let mut line_start = captures.get(0).unwrap().end();
+    for line in input[line_start..].split_inclusive('\n') {
+        let line_end = line_start + line.len();
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.trim_end_matches([' ', '\t']) == fence {
+            input.replace_range(start..line_end, "");
+            return Ok(Some(start..line_end));
         }
+        line_start = line_end;
     }
+    Err("unterminated frontmatter".into())
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,130 @@
+use super::{clean_with_report, CleaningMode};
+
+fn clean(input: &str) -> (String, super::CleaningReport) {
+    clean_with_report(input, CleaningMode::CLEAN_SHEBANG).unwrap()
+}
+
+#[test]
+fn leading_bom_is_removed() {
+    let (cleaned, report) = clean("\u{feff}abc");
+    assert_eq!(cleaned, "abc");
+    assert!(report.removed_bom);
+}
+
+#[test]
+fn only_leading_bom_is_removed() {
+    let (cleaned, report) = clean("\u{feff}\u{feff}abc");
+    assert_eq!(cleaned, "\u{feff}abc");
+    assert!(report.removed_bom);
+
+    let (cleaned, report) = clean("a\u{feff}bc");
+    assert_eq!(cleaned, "a\u{feff}bc");
+    assert!(!report.removed_bom);
+}
+
+#[test]
+fn bom_removal_is_position_accurate() {
+    // A span in the cleaned input is 3 bytes (the length of the BOM) behind the original input.
+    let input = "\u{feff}abc def";
+    let tokens = crate::lex(input, crate::Edition::E2021).unwrap();
+    let last = tokens.last().unwrap();
+    assert_eq!(last.span.start + 3, input.find("def").unwrap());
+    assert_eq!(&input[last.span.start + 3..last.span.end + 3], "def");
+}
+
+#[test]
+fn bom_is_removed_before_shebang() {
+    let (cleaned, report) = clean("\u{feff}#!/bin/sh\nfn");
+    assert_eq!(cleaned, "fn");
+    assert!(report.removed_bom);
+    assert_eq!(report.removed_shebang.as_deref(), Some("#!/bin/sh\n"));
+}
+
+#[test]
+fn crlf_is_converted_but_lone_cr_is_kept() {
+    let (cleaned, report) = clean("a\r\nb\rc\r\r\n");
+    assert_eq!(cleaned, "a\nb\rc\r\n");
+    assert_eq!(report.converted_crlfs, 2);
+}
+
+#[test]
+fn shebang_is_removed_up_to_and_including_the_first_newline() {
+    for (input, removed, rest) in [
+        ("#!/x\nfn", "#!/x\n", "fn"),
+        ("#!/x\r\nfn", "#!/x\n", "fn"),
+        ("#!\nfn", "#!\n", "fn"),
+        ("#!\t/x\nfn", "#!\t/x\n", "fn"),
+        ("#!/x", "#!/x", ""),
+    ] {
+        let (cleaned, report) = clean(input);
+        assert_eq!(
+            report.removed_shebang.as_deref(),
+            Some(removed),
+            "{input:?}"
+        );
+        assert_eq!(cleaned, rest, "{input:?}");
+    }
+}
+
+#[test]
+fn shebang_must_be_at_the_start() {
+    for input in [" #!/x", "\n#!/x\nfn", "\t#!/x"] {
+        let (cleaned, report) = clean(input);
+        assert_eq!(report.removed_shebang, None, "{input:?}");
+        assert_eq!(cleaned, input);
+    }
+}
+
+#[test]
+fn attribute_is_not_a_shebang() {
+    for input in ["#![attr]\nfn", "#!\t[attr]", "#!\n[attr]"] {
+        let (cleaned, report) = clean(input);
+        assert_eq!(report.removed_shebang, None, "{input:?}");
+        assert_eq!(cleaned, input);
+    }
+}
+
+#[test]
+fn cleaning_steps_are_independent() {
+    let input = "\u{feff}#!/bin/sh\r\nfn\r\n";
+    let only = |mode| clean_with_report(input, mode).unwrap().0;
+    assert_eq!(only(CleaningMode::NO_CLEANING), input);
+    let strip_bom = CleaningMode {
+        strip_bom: true,
+        ..CleaningMode::NO_CLEANING
+    };
+    assert_eq!(only(strip_bom), "#!/bin/sh\r\nfn\r\n");
+    let normalise_crlf = CleaningMode {
+        normalise_crlf: true,
+        ..CleaningMode::NO_CLEANING
+    };
+    assert_eq!(only(normalise_crlf), "\u{feff}#!/bin/sh\nfn\n");
+    // The shebang isn't at the start of the input until the BOM has been removed
+    let strip_shebang = CleaningMode {
+        strip_shebang: true,
+        ..CleaningMode::NO_CLEANING
+    };
+    assert_eq!(only(strip_shebang), input);
+    let strip_bom_and_shebang = CleaningMode {
+        strip_shebang: true,
+        ..strip_bom
+    };
+    assert_eq!(only(strip_bom_and_shebang), "fn\r\n");
+}
+
+#[test]
+fn frontmatter_with_crlf_is_removed_without_converting_crlf() {
+    let strip_frontmatter = CleaningMode {
+        strip_frontmatter: true,
+        ..CleaningMode::NO_CLEANING
+    };
+    let input = "\r\n---cargo\r\n[dependencies]\r\n---\r\nfn\r\n";
+    let (cleaned, _) = clean_with_report(input, strip_frontmatter).unwrap();
+    assert_eq!(cleaned, "\r\nfn\r\n");
+    let with_crlf_converted = CleaningMode {
+        normalise_crlf: true,
+        ..strip_frontmatter
+    };
+    let (cleaned, _) = clean_with_report(input, with_crlf_converted).unwrap();
+    assert_eq!(cleaned, "\nfn\n");
+}
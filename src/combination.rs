@@ -5,6 +5,10 @@
 //!
 //! This representation doesn't have explicit whitespace tokens. It has explicit [`Spacing`]
 //! information instead.
+//!
+//! Combination doesn't need to know about the forms which Rust 2024 reserves (such as `##` and
+//! `#"..."#`): lexical analysis rejects input containing them, so they never reach this module.
+//! In earlier editions they're ordinary `#` punctuation marks, which are never combined.
 
 use crate::char_sequences::{concat_charseqs, Charseq};
 use crate::lexlucid::{self, CommentStyle, FineToken, FineTokenData};
@@ -130,6 +134,24 @@ pub fn coarsen(tokens: impl IntoIterator<Item = FineToken>) -> Vec<CoarseToken>
     combine(process_whitespace(tokens))
 }
 
+/// Reconstructs source text for a sequence of `CoarseToken`s from their extents.
+///
+/// Tokens with [`Alone`][Spacing::Alone] spacing are followed by a single space (or by a newline,
+/// for line comments). Lexing the result should give the same coarse tokens.
+pub fn restringify(tokens: &[CoarseToken]) -> String {
+    let mut s = String::new();
+    for (i, token) in tokens.iter().enumerate() {
+        s.extend(token.extent.chars());
+        if token.spacing == Spacing::Alone && i + 1 < tokens.len() {
+            s.push(match token.data {
+                CoarseTokenData::LineComment { .. } => '\n',
+                _ => ' ',
+            });
+        }
+    }
+    s
+}
+
 /// Calculates spacing information for fine-grained tokens, dropping tokens representing whitespace.
 fn process_whitespace(tokens: impl IntoIterator<Item = FineToken>) -> Vec<(FineToken, Spacing)> {
     let mut processed = Vec::new();
@@ -319,11 +341,13 @@ impl TryFrom<FineTokenData> for CoarseTokenData {
             }
             FineTokenData::Identifier {
                 represented_identifier,
+                ..
             } => Ok(CoarseTokenData::Identifier {
                 represented_identifier,
             }),
             FineTokenData::RawIdentifier {
                 represented_identifier,
+                ..
             } => Ok(CoarseTokenData::RawIdentifier {
                 represented_identifier,
             }),
@@ -350,6 +374,7 @@ impl TryFrom<FineTokenData> for CoarseTokenData {
             FineTokenData::StringLiteral {
                 represented_string,
                 suffix,
+                ..
             } => Ok(CoarseTokenData::StringLiteral {
                 represented_string,
                 suffix,
@@ -357,6 +382,7 @@ impl TryFrom<FineTokenData> for CoarseTokenData {
             FineTokenData::ByteStringLiteral {
                 represented_bytes,
                 suffix,
+                ..
             } => Ok(CoarseTokenData::ByteStringLiteral {
                 represented_bytes,
                 suffix,
@@ -364,6 +390,7 @@ impl TryFrom<FineTokenData> for CoarseTokenData {
             FineTokenData::CStringLiteral {
                 represented_bytes,
                 suffix,
+                ..
             } => Ok(CoarseTokenData::CStringLiteral {
                 represented_bytes,
                 suffix,
@@ -393,12 +420,13 @@ impl TryFrom<FineTokenData> for CoarseTokenData {
                 base,
                 digits,
                 suffix,
+                ..
             } => Ok(CoarseTokenData::IntegerLiteral {
                 base: base.into(),
                 digits,
                 suffix,
             }),
-            FineTokenData::FloatLiteral { body, suffix } => {
+            FineTokenData::FloatLiteral { body, suffix, .. } => {
                 Ok(CoarseTokenData::FloatLiteral { body, suffix })
             }
         }
@@ -415,3 +443,6 @@ impl From<lexlucid::NumericBase> for NumericBase {
         }
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,63 @@
+use crate::lexlucid::{self, Analysis};
+use crate::Edition;
+
+use super::{coarsen, Spacing};
+
+/// Lexes and coarsens the input, returning each coarse token's extent and spacing.
+fn coarse_extents(input: &str) -> Vec<(String, Spacing)> {
+    let Analysis::Accepts(_, tokens) = lexlucid::analyse(input, Edition::E2021) else {
+        panic!("{input} should be accepted");
+    };
+    coarsen(tokens)
+        .into_iter()
+        .map(|token| (token.extent.to_string(), token.spacing))
+        .collect()
+}
+
+#[test]
+fn range_punctuation_spacing() {
+    use Spacing::*;
+    for (input, expected) in [
+        ("a..b", vec![("a", Joint), ("..", Joint), ("b", Alone)]),
+        ("a..=b", vec![("a", Joint), ("..=", Joint), ("b", Alone)]),
+        ("a...b", vec![("a", Joint), ("...", Joint), ("b", Alone)]),
+        ("0..1", vec![("0", Joint), ("..", Joint), ("1", Alone)]),
+        ("1.0..2", vec![("1.0", Joint), ("..", Joint), ("2", Alone)]),
+        ("..", vec![("..", Alone)]),
+        ("x..", vec![("x", Joint), ("..", Alone)]),
+        ("....", vec![("...", Joint), (".", Alone)]),
+        ("..==", vec![("..=", Joint), ("=", Alone)]),
+        (".. =", vec![("..", Alone), ("=", Alone)]),
+    ] {
+        let expected: Vec<_> = expected
+            .into_iter()
+            .map(|(extent, spacing)| (extent.to_owned(), spacing))
+            .collect();
+        assert_eq!(coarse_extents(input), expected, "{input}");
+    }
+}
+
+#[test]
+fn doc_comment_spacing() {
+    use Spacing::*;
+    for (input, expected) in [
+        ("///x\nfoo", vec![("///x", Alone), ("foo", Alone)]),
+        ("a///x", vec![("a", Joint), ("///x", Alone)]),
+        ("/**x*/foo", vec![("/**x*/", Joint), ("foo", Alone)]),
+        ("/**x*/.", vec![("/**x*/", Joint), (".", Alone)]),
+        (
+            "a /**x*/ b",
+            vec![("a", Alone), ("/**x*/", Alone), ("b", Alone)],
+        ),
+        (
+            "a/*x*//**y*/b",
+            vec![("a", Alone), ("/**y*/", Joint), ("b", Alone)],
+        ),
+    ] {
+        let expected: Vec<_> = expected
+            .into_iter()
+            .map(|(extent, spacing)| (extent.to_owned(), spacing))
+            .collect();
+        assert_eq!(coarse_extents(input), expected, "{input}");
+    }
+}
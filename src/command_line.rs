@@ -1,32 +1,188 @@
 //! Command-line processing.
 
+use std::io::Read;
+
+use crate::cleaning::CleaningMode;
+use crate::confusables::run_confusables_subcommand;
+use crate::coverage::{run_coverage_subcommand, run_priority_audit_subcommand};
+use crate::lexlucid;
 use crate::proptesting::{self, Verbosity};
+use crate::regular_tokens::TokenKind;
+use crate::rustc_recording::{self, run_record_rustc_subcommand};
 use crate::simple_reports::{
-    run_coarse_subcommand, run_compare_subcommand, run_inspect_subcommand, DetailsMode,
+    run_check_subcommand, run_coarse_subcommand, run_compare_subcommand, run_inspect_subcommand,
+    run_list_testcases_subcommand, run_selfcheck_subcommand, run_tokenize_file_subcommand,
+    CompareOptions, DetailsMode, DiffKind, InspectFormat, InspectOptions,
 };
 use crate::testcases;
 use crate::Edition;
 
 const USAGE: &str = "\
-Usage: lexeywan [--edition=2015|2021|2024] [<subcommand>] [...options]
+Usage: lexeywan [--edition=2015|2018|2021|2024] [--max-input-length=<n>] [--trace]
+                [<subcommand>] [...options]
 
 Subcommands:
- *compare  [--short] [--failures-only] [--details=always|*failures|never]
-  inspect  [--short]
-  coarse   [--short]
+ *compare  [--short|--stdin|--corpus-dir=<path>] [--failures-only|--quiet]
+           [--details=always|*failures|never]
+           [--model-error-report] [--summarize-rejections]
+           [--time-inputs] [--slow-inputs=<ms>] [--jobs=<n>]
+           [--same-rejection-category] [--panic-on-model-error] [--fail-fast]
+           [--csv=<path>]
+           [--only=strings|idents|numerics|comments|punctuation|lifetimes]
+           [--diff-only-kind=literals] [--replay=<path>]
+  inspect  [--short|--stdin] [--time-inputs]
+           [--format=*text|json|ra-kinds|sexpr]
+           [--cleaning=none|*shebang|shebang-and-frontmatter]
+           [--clean=bom,crlf,shebang,frontmatter] [--no-cleaning]
+           [--panic-on-model-error] [--all-editions] [--explain]
+  coarse   [--short|--stdin] [--count-tokens] [--roundtrip]
+  check    --expect=accept|reject <input>
+  list-testcases [--short|--corpus-dir=<path>] [--format=*text|json]
+  record-rustc --output=<path> [--short|--stdin|--corpus-dir=<path>]
+  selfcheck [--short|--stdin|--corpus-dir=<path>] [--all-editions]
+  tokenize-file [--format=*text|json|ra-kinds|sexpr] [--cleaning=...] <path>
+  confusables [--short|--stdin]
+  coverage [--short|--stdin]
+  audit-priority [--short|--stdin]
   proptest [--count] [--strategy=<name>] [--print-failures|--print-all]
+           [--seed=<n>]
 
 * -- default
 
+--edition=2018: the same as --edition=2015 (Rust 2018 has the same lexical
+  rules as Rust 2015)
+--max-input-length: refuse inputs longer than <n> bytes (default 100000);
+  proptest skips generated inputs which are too long (tokenize-file isn't
+  limited)
+--trace: print each pretokenisation rule which matched (and the length of its
+  match) and the rule chosen, for every pretoken lexlucid extracts
 --short: run the SHORTLIST rather than the LONGLIST
+--stdin: read a single input from standard input (it's still cleaned, so a
+  byte order mark or shebang is removed and CRLF is converted to LF)
+--corpus-dir: read one input from each .rs file in <path> (and its
+  subdirectories), skipping files longer than --max-input-length or not UTF-8;
+  compare identifies each input by its file name (the other subcommands which
+  take --short or --stdin accept this too)
+--strategy: a proptest strategy; in addition to the character-soup strategies,
+  'string-literals' makes structured string-family literals with escapes,
+  'raw-strings' makes raw string literals with up to six hashes whose content
+  nearly closes them early, and 'mutate' makes small edits to the LONGLIST
+  testcases
+--seed: seed proptest's random number generator with <n> (a u64), to reproduce
+  an earlier run; without it a random seed is chosen, and reported on failure
+--quiet: like --failures-only, but also omit the summary (printing nothing at
+  all) if rustc and lexlucid agree on every input
+--summarize-rejections: after the results, show how many times lexlucid gave
+  each reason for rejecting an input, and which rejection categories it never
+  used
+--time-inputs: show how long each stage of processing took for each input
+--slow-inputs: after the results, list the inputs which lexlucid took longer
+  than <ms> milliseconds to lex, slowest first
+--jobs: lex the inputs using <n> worker threads (results are still shown in order)
+--same-rejection-category: when both rustc and lexlucid reject an input, treat
+  them as disagreeing if their reasons fall in different categories (rustc's
+  category is guessed from its messages, and is ignored if it can't be)
+--panic-on-model-error: stop with a panic at the first input for which lexlucid
+  reports a model error (set RUST_BACKTRACE=1 to see where)
+--fail-fast: stop after the first input for which rustc and lexlucid don't
+  agree (or either reports a model error)
+--csv: also write one CSV row per input to <path>, giving the input (escaped as
+  in the report), the rustc and lexlucid verdict symbols, and the comparison
+  (each row is written as soon as its input has been compared)
+--only: consider only inputs whose first token is of the specified kind
+--diff-only-kind=literals: compare only the represented values of string-family
+  literals, ignoring all other tokens, spacing, and extents (to separate
+  unescaping problems from tokenisation problems)
+--replay: take rustc's output from a recording made by record-rustc (in the
+  same edition) instead of running rustc; inputs which aren't in the recording
+  are reported as rustc model errors
+--roundtrip: check that lexing the coarse tokens' text, separated by single
+  spaces where there was spacing, gives the same coarse tokens
+--count-tokens: print counts of each kind of coarse token instead of the tokens
+--cleaning=shebang-and-frontmatter: also remove a cargo-script frontmatter block
+  before lexing with lexlucid (rustc's input is unaffected); --cleaning=none
+  (or --no-cleaning) makes no changes at all
+--clean: make only the listed cleaning steps (any of: bom, to remove a leading
+  byte order mark; crlf, to convert CRLF to LF; shebang; frontmatter), to see
+  which step causes a difference; --clean= makes no changes
+--all-editions: lex each input with lexlucid in every edition (instead of
+  comparing with rustc), marking where the editions' tokens diverge (for
+  selfcheck: check each input in every edition)
+--explain: show the name of the pretokenisation rule which produced each of
+  lexlucid's tokens
+--format=json: print lexlucid's tokens for each input as a line of JSON
+  (list-testcases prints each input as a JSON string, without lexing it)
+--format=ra-kinds: print lexlucid's tokens in the form of rustc_lexer's Token
+  Debug output (kind names and byte lengths), for comparing with rust-analyzer
+--format=sexpr: print lexlucid's tokens for each input as an S-expression, with
+  the tokens between matching delimiters nested in groups (whitespace and
+  non-doc comments are omitted)
+
+check exits with status 0 if lexlucid accepts or rejects <input> as expected, 1
+if it doesn't, and 3 if lexlucid reports a model error (it prints nothing if
+the check passes).
+
+The LEXEYWAN_DIALECT environment variable may hold --edition and --cleaning
+options (separated by whitespace), which are used when the same option isn't
+given on the command line.
+
+selfcheck lexes each input with lexlucid, reporting any model errors (which
+should never happen), and exits with status 3 if there were any.
+
+record-rustc writes rustc's regularised tokens (or rejection messages) for each
+input to <path>, and exits with status 4 if the file can't be written.
+
+tokenize-file exits with status 1 if lexlucid rejects the file, 3 if lexlucid
+reports a model error, and 4 if the file can't be read.
 
 ";
 
 const DEFAULT_PROPTEST_COUNT: u32 = 5000;
 
+/// Environment variable holding default dialect options.
+const DIALECT_VARIABLE: &str = "LEXEYWAN_DIALECT";
+
+/// Dialect options taken from the `LEXEYWAN_DIALECT` environment variable.
+///
+/// These are used when the corresponding option isn't given on the command line.
+#[derive(Default)]
+struct DialectDefaults {
+    edition: Option<String>,
+    cleaning: Option<String>,
+}
+
+impl DialectDefaults {
+    /// Reads the options from the environment variable, which holds `--edition` and `--cleaning`
+    /// options in the same form as on the command line, separated by whitespace.
+    fn from_env() -> Result<Self, pico_args::Error> {
+        let Some(value) = std::env::var_os(DIALECT_VARIABLE) else {
+            return Ok(Self::default());
+        };
+        let Some(value) = value.to_str() else {
+            return Err(pico_args::Error::ArgumentParsingFailed {
+                cause: format!("{DIALECT_VARIABLE} isn't valid UTF-8"),
+            });
+        };
+        let mut args =
+            pico_args::Arguments::from_vec(value.split_whitespace().map(Into::into).collect());
+        let dialect = Self {
+            edition: args.opt_value_from_str("--edition")?,
+            cleaning: args.opt_value_from_str("--cleaning")?,
+        };
+        if !args.finish().is_empty() {
+            return Err(pico_args::Error::ArgumentParsingFailed {
+                cause: format!("{DIALECT_VARIABLE} may contain only --edition and --cleaning"),
+            });
+        }
+        Ok(dialect)
+    }
+}
+
+const DEFAULT_MAX_INPUT_LENGTH: usize = 100_000;
+
 pub fn run_cli() -> impl std::process::Termination {
     match run_cli_impl() {
-        Ok(_) => std::process::ExitCode::from(0),
+        Ok(status) => std::process::ExitCode::from(status),
         Err(pico_args::Error::ArgumentParsingFailed { cause }) => {
             eprint!("{USAGE}{cause}\n");
             std::process::ExitCode::from(2)
@@ -37,19 +193,32 @@ pub fn run_cli() -> impl std::process::Termination {
         }
     }
 }
-fn run_cli_impl() -> Result<(), pico_args::Error> {
+
+/// Tells the user that `--edition=2018` is being treated as 2015.
+///
+/// This goes to standard error, so that it doesn't get mixed up with JSON output.
+fn print_2018_note() {
+    eprintln!("note: Rust 2018 has the same lexical rules as Rust 2015, so using edition 2015");
+}
+
+/// Runs the requested subcommand, returning the exit status.
+fn run_cli_impl() -> Result<u8, pico_args::Error> {
     let mut args = pico_args::Arguments::from_env();
 
     if args.contains("--help") {
         print!("{}", USAGE);
-        return Ok(());
+        return Ok(0);
     }
 
-    let edition = match args
+    let dialect = DialectDefaults::from_env()?;
+
+    let requested_edition = args
         .opt_value_from_str::<_, String>("--edition")?
-        .as_deref()
-    {
-        Some("2015") => Edition::E2015,
+        .or(dialect.edition);
+    // Rust 2018 didn't change any lexical rules, so it's treated as 2015
+    let is_2018 = requested_edition.as_deref() == Some("2018");
+    let edition = match requested_edition.as_deref() {
+        Some("2015" | "2018") => Edition::E2015,
         Some("2021") => Edition::E2021,
         Some("2024") => Edition::E2024,
         None => Edition::E2021,
@@ -60,34 +229,196 @@ fn run_cli_impl() -> Result<(), pico_args::Error> {
         }
     };
 
-    fn requested_inputs(args: &mut pico_args::Arguments) -> &'static [&'static str] {
-        if args.contains("--short") {
-            testcases::SHORTLIST
+    if args.contains("--trace") {
+        lexlucid::set_rule_tracing(true);
+    }
+
+    let max_input_length = args
+        .opt_value_from_str::<_, usize>("--max-input-length")?
+        .unwrap_or(DEFAULT_MAX_INPUT_LENGTH);
+
+    fn requested_inputs(
+        args: &mut pico_args::Arguments,
+        max_input_length: usize,
+    ) -> Result<Vec<String>, pico_args::Error> {
+        Ok(requested_labelled_inputs(args, max_input_length)?.0)
+    }
+
+    /// As `requested_inputs`, but also returns the name of each input's file if the inputs were
+    /// read with --corpus-dir.
+    fn requested_labelled_inputs(
+        args: &mut pico_args::Arguments,
+        max_input_length: usize,
+    ) -> Result<(Vec<String>, Option<Vec<String>>), pico_args::Error> {
+        if let Some(dir) = args.opt_value_from_str::<_, std::path::PathBuf>("--corpus-dir")? {
+            let files = testcases::read_corpus_dir(&dir, max_input_length).map_err(|e| {
+                pico_args::Error::ArgumentParsingFailed {
+                    cause: format!("can't read corpus directory {}: {e}", dir.display()),
+                }
+            })?;
+            let (paths, inputs): (Vec<_>, Vec<_>) = files.into_iter().unzip();
+            let labels = paths
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect();
+            return Ok((inputs, Some(labels)));
+        }
+        let inputs = if args.contains("--stdin") {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input).map_err(|e| {
+                pico_args::Error::ArgumentParsingFailed {
+                    cause: format!("can't read standard input: {e}"),
+                }
+            })?;
+            vec![input]
         } else {
-            testcases::LONGLIST
+            let testcases = if args.contains("--short") {
+                testcases::SHORTLIST
+            } else {
+                testcases::LONGLIST
+            };
+            testcases.iter().map(|s| s.to_string()).collect()
+        };
+        if let Some(input) = inputs.iter().find(|input| input.len() > max_input_length) {
+            return Err(pico_args::Error::ArgumentParsingFailed {
+                cause: format!(
+                    "an input is {} bytes long (the limit is {max_input_length})",
+                    input.len()
+                ),
+            });
+        }
+        Ok((inputs, None))
+    }
+
+    fn requested_format(
+        args: &mut pico_args::Arguments,
+    ) -> Result<InspectFormat, pico_args::Error> {
+        match args.opt_value_from_str::<_, String>("--format")?.as_deref() {
+            Some("text") => Ok(InspectFormat::Text),
+            Some("json") => Ok(InspectFormat::Json),
+            Some("ra-kinds") => Ok(InspectFormat::RaKinds),
+            Some("sexpr") => Ok(InspectFormat::Sexpr),
+            None => Ok(InspectFormat::Text),
+            _ => Err(pico_args::Error::ArgumentParsingFailed {
+                cause: "unknown format".into(),
+            }),
         }
     }
 
+    fn requested_cleaning_mode(
+        args: &mut pico_args::Arguments,
+        default: Option<&str>,
+    ) -> Result<CleaningMode, pico_args::Error> {
+        let preset = args.opt_value_from_str::<_, String>("--cleaning")?;
+        let steps = args.opt_value_from_str::<_, String>("--clean")?;
+        let no_cleaning = args.contains("--no-cleaning");
+        if [preset.is_some(), steps.is_some(), no_cleaning]
+            .iter()
+            .filter(|given| **given)
+            .count()
+            > 1
+        {
+            return Err(pico_args::Error::ArgumentParsingFailed {
+                cause: "only one of --cleaning, --clean, and --no-cleaning may be given".into(),
+            });
+        }
+        if no_cleaning {
+            return Ok(CleaningMode::NO_CLEANING);
+        }
+        if let Some(steps) = steps {
+            return requested_cleaning_steps(&steps);
+        }
+        match preset.as_deref().or(default) {
+            Some("none") => Ok(CleaningMode::NO_CLEANING),
+            Some("shebang") => Ok(CleaningMode::CLEAN_SHEBANG),
+            Some("shebang-and-frontmatter") => Ok(CleaningMode::CLEAN_SHEBANG_AND_FRONTMATTER),
+            None => Ok(CleaningMode::CLEAN_SHEBANG),
+            _ => Err(pico_args::Error::ArgumentParsingFailed {
+                cause: "unknown cleaning mode".into(),
+            }),
+        }
+    }
+
+    /// Parses the comma-separated list of cleaning steps given with `--clean`.
+    fn requested_cleaning_steps(steps: &str) -> Result<CleaningMode, pico_args::Error> {
+        let mut mode = CleaningMode::NO_CLEANING;
+        for step in steps.split(',').filter(|step| !step.is_empty()) {
+            match step {
+                "bom" => mode.strip_bom = true,
+                "crlf" => mode.normalise_crlf = true,
+                "shebang" => mode.strip_shebang = true,
+                "frontmatter" => mode.strip_frontmatter = true,
+                _ => {
+                    return Err(pico_args::Error::ArgumentParsingFailed {
+                        cause: format!("unknown cleaning step: {step}"),
+                    })
+                }
+            }
+        }
+        Ok(mode)
+    }
+
     enum Action {
         Compare {
-            inputs: &'static [&'static str],
-            show_failures_only: bool,
-            details_mode: DetailsMode,
+            inputs: Vec<String>,
+            options: CompareOptions,
         },
         Inspect {
-            inputs: &'static [&'static str],
+            inputs: Vec<String>,
+            options: InspectOptions,
         },
         Coarse {
-            inputs: &'static [&'static str],
+            inputs: Vec<String>,
+            count_tokens: bool,
+            roundtrip: bool,
+        },
+        Check {
+            input: String,
+            expect_accept: bool,
+        },
+        TokenizeFile {
+            path: std::path::PathBuf,
+            format: InspectFormat,
+            cleaning_mode: CleaningMode,
+        },
+        Confusables {
+            inputs: Vec<String>,
+        },
+        Coverage {
+            inputs: Vec<String>,
+        },
+        AuditPriority {
+            inputs: Vec<String>,
+        },
+        ListTestcases {
+            inputs: Vec<String>,
+            format: InspectFormat,
+        },
+        RecordRustc {
+            inputs: Vec<String>,
+            path: std::path::PathBuf,
+        },
+        Selfcheck {
+            inputs: Vec<String>,
+            all_editions: bool,
         },
         PropTest {
             strategy_name: String,
             count: u32,
             verbosity: Verbosity,
+            seed: Option<u64>,
         },
     }
-    fn compare_action(args: &mut pico_args::Arguments) -> Result<Action, pico_args::Error> {
+    fn compare_action(
+        args: &mut pico_args::Arguments,
+        edition: Edition,
+        max_input_length: usize,
+    ) -> Result<Action, pico_args::Error> {
         let show_failures_only = args.contains("--failures-only");
+        let quiet = args.contains("--quiet");
+        let show_model_error_report = args.contains("--model-error-report");
+        let show_rejection_report = args.contains("--summarize-rejections");
+        let show_timings = args.contains("--time-inputs");
         let details_mode = match args
             .opt_value_from_str::<_, String>("--details")?
             .as_deref()
@@ -102,19 +433,157 @@ fn run_cli_impl() -> Result<(), pico_args::Error> {
                 })
             }
         };
+        let diff_kind = match args
+            .opt_value_from_str::<_, String>("--diff-only-kind")?
+            .as_deref()
+        {
+            Some("literals") => DiffKind::LiteralValues,
+            None => DiffKind::Everything,
+            _ => {
+                return Err(pico_args::Error::ArgumentParsingFailed {
+                    cause: "unknown diff kind".into(),
+                })
+            }
+        };
+        let only = match args.opt_value_from_str::<_, String>("--only")?.as_deref() {
+            Some("strings") => Some(TokenKind::Strings),
+            Some("idents") => Some(TokenKind::Identifiers),
+            Some("numerics") => Some(TokenKind::Numerics),
+            Some("comments") => Some(TokenKind::Comments),
+            Some("punctuation") => Some(TokenKind::Punctuation),
+            Some("lifetimes") => Some(TokenKind::Lifetimes),
+            None => None,
+            _ => {
+                return Err(pico_args::Error::ArgumentParsingFailed {
+                    cause: "unknown token kind".into(),
+                })
+            }
+        };
+        let jobs = args.opt_value_from_str::<_, usize>("--jobs")?.unwrap_or(1);
+        let slow_threshold = args
+            .opt_value_from_str::<_, u64>("--slow-inputs")?
+            .map(std::time::Duration::from_millis);
+        if jobs == 0 {
+            return Err(pico_args::Error::ArgumentParsingFailed {
+                cause: "--jobs must be at least 1".into(),
+            });
+        }
+        let same_rejection_category = args.contains("--same-rejection-category");
+        let panic_on_model_error = args.contains("--panic-on-model-error");
+        let fail_fast = args.contains("--fail-fast");
+        let csv_path = args.opt_value_from_str::<_, std::path::PathBuf>("--csv")?;
+        let replay =
+            match args.opt_value_from_str::<_, std::path::PathBuf>("--replay")? {
+                Some(path) => Some(rustc_recording::read_recording(&path, edition).map_err(
+                    |e| pico_args::Error::ArgumentParsingFailed {
+                        cause: format!("can't replay {}: {e}", path.display()),
+                    },
+                )?),
+                None => None,
+            };
+        let (inputs, input_labels) = requested_labelled_inputs(args, max_input_length)?;
         Ok(Action::Compare {
-            inputs: requested_inputs(args),
-            show_failures_only,
-            details_mode,
+            inputs,
+            options: CompareOptions {
+                details_mode,
+                diff_kind,
+                show_failures_only,
+                quiet,
+                show_model_error_report,
+                show_rejection_report,
+                show_timings,
+                slow_threshold,
+                only,
+                jobs,
+                same_rejection_category,
+                panic_on_model_error,
+                fail_fast,
+                csv_path,
+                input_labels,
+                replay,
+            },
         })
     }
     let action = match args.subcommand()?.as_deref() {
-        Some("compare") => compare_action(&mut args)?,
-        Some("inspect") => Action::Inspect {
-            inputs: requested_inputs(&mut args),
+        Some("compare") => compare_action(&mut args, edition, max_input_length)?,
+        Some("inspect") => {
+            let options = InspectOptions {
+                format: requested_format(&mut args)?,
+                show_timings: args.contains("--time-inputs"),
+                cleaning_mode: requested_cleaning_mode(&mut args, dialect.cleaning.as_deref())?,
+                panic_on_model_error: args.contains("--panic-on-model-error"),
+                all_editions: args.contains("--all-editions"),
+                explain: args.contains("--explain"),
+            };
+            if options.all_editions && options.format != InspectFormat::Text {
+                return Err(pico_args::Error::ArgumentParsingFailed {
+                    cause: "--all-editions can only be used with --format=text".into(),
+                });
+            }
+            if options.explain && (options.all_editions || options.format != InspectFormat::Text) {
+                return Err(pico_args::Error::ArgumentParsingFailed {
+                    cause: "--explain can only be used with --format=text (and not --all-editions)"
+                        .into(),
+                });
+            }
+            Action::Inspect {
+                options,
+                inputs: requested_inputs(&mut args, max_input_length)?,
+            }
+        }
+        Some("tokenize-file") => Action::TokenizeFile {
+            format: requested_format(&mut args)?,
+            cleaning_mode: requested_cleaning_mode(&mut args, dialect.cleaning.as_deref())?,
+            path: args.free_from_str()?,
+        },
+        Some("check") => {
+            let expect_accept = match args.value_from_str::<_, String>("--expect")?.as_str() {
+                "accept" => true,
+                "reject" => false,
+                _ => {
+                    return Err(pico_args::Error::ArgumentParsingFailed {
+                        cause: "--expect must be accept or reject".into(),
+                    })
+                }
+            };
+            Action::Check {
+                input: args.free_from_str()?,
+                expect_accept,
+            }
+        }
+        Some("confusables") => Action::Confusables {
+            inputs: requested_inputs(&mut args, max_input_length)?,
+        },
+        Some("coverage") => Action::Coverage {
+            inputs: requested_inputs(&mut args, max_input_length)?,
+        },
+        Some("audit-priority") => Action::AuditPriority {
+            inputs: requested_inputs(&mut args, max_input_length)?,
+        },
+        Some("list-testcases") => {
+            let format = requested_format(&mut args)?;
+            if matches!(format, InspectFormat::RaKinds | InspectFormat::Sexpr) {
+                return Err(pico_args::Error::ArgumentParsingFailed {
+                    cause: "list-testcases supports only --format=text and --format=json".into(),
+                });
+            }
+            Action::ListTestcases {
+                format,
+                inputs: requested_inputs(&mut args, max_input_length)?,
+            }
+        }
+        Some("selfcheck") => Action::Selfcheck {
+            all_editions: args.contains("--all-editions"),
+            inputs: requested_inputs(&mut args, max_input_length)?,
+        },
+        Some("record-rustc") => Action::RecordRustc {
+            path: args.value_from_str("--output")?,
+            inputs: requested_inputs(&mut args, max_input_length)?,
         },
         Some("coarse") => Action::Coarse {
-            inputs: requested_inputs(&mut args),
+            count_tokens: args.contains("--count-tokens"),
+            roundtrip: args.contains("--roundtrip"),
+            inputs: requested_inputs(&mut args, max_input_length)?,
         },
         Some("proptest") => {
             let strategy_name = args
@@ -141,13 +610,15 @@ fn run_cli_impl() -> Result<(), pico_args::Error> {
             } else {
                 Verbosity::Quiet
             };
+            let seed = args.opt_value_from_str::<_, u64>("--seed")?;
             Action::PropTest {
                 strategy_name,
                 count,
                 verbosity,
+                seed,
             }
         }
-        None => compare_action(&mut args)?,
+        None => compare_action(&mut args, edition, max_input_length)?,
         _ => {
             return Err(pico_args::Error::ArgumentParsingFailed {
                 cause: "unknown subcommand".into(),
@@ -161,20 +632,84 @@ fn run_cli_impl() -> Result<(), pico_args::Error> {
         });
     }
 
+    fn as_strs(inputs: &[String]) -> Vec<&str> {
+        inputs.iter().map(String::as_str).collect()
+    }
     match action {
-        Action::Compare {
+        Action::Compare { inputs, options } => {
+            if is_2018 {
+                print_2018_note();
+            }
+            run_compare_subcommand(&as_strs(&inputs), edition, &options)
+        }
+        Action::Inspect { inputs, options } => {
+            if is_2018 {
+                print_2018_note();
+            }
+            run_inspect_subcommand(&as_strs(&inputs), edition, &options)
+        }
+        Action::Coarse {
             inputs,
-            show_failures_only,
-            details_mode,
-        } => run_compare_subcommand(inputs, edition, details_mode, show_failures_only),
-        Action::Inspect { inputs } => run_inspect_subcommand(inputs, edition),
-        Action::Coarse { inputs } => run_coarse_subcommand(inputs, edition),
+            count_tokens,
+            roundtrip,
+        } => run_coarse_subcommand(&as_strs(&inputs), edition, count_tokens, roundtrip),
         Action::PropTest {
             strategy_name,
             count,
             verbosity,
-        } => proptesting::run_proptests(&strategy_name, count, verbosity, edition),
+            seed,
+        } => proptesting::run_proptests(
+            &strategy_name,
+            count,
+            verbosity,
+            seed,
+            edition,
+            max_input_length,
+        ),
+        Action::Confusables { inputs } => run_confusables_subcommand(&as_strs(&inputs), edition),
+        Action::Coverage { inputs } => run_coverage_subcommand(&as_strs(&inputs), edition),
+        Action::AuditPriority { inputs } => {
+            run_priority_audit_subcommand(&as_strs(&inputs), edition)
+        }
+        Action::ListTestcases { inputs, format } => {
+            run_list_testcases_subcommand(&as_strs(&inputs), format)
+        }
+        Action::Selfcheck {
+            inputs,
+            all_editions,
+        } => {
+            return Ok(run_selfcheck_subcommand(
+                &as_strs(&inputs),
+                edition,
+                all_editions,
+            ));
+        }
+        Action::RecordRustc { inputs, path } => {
+            return Ok(run_record_rustc_subcommand(
+                &as_strs(&inputs),
+                edition,
+                &path,
+            ));
+        }
+        Action::Check {
+            input,
+            expect_accept,
+        } => {
+            return Ok(run_check_subcommand(&input, edition, expect_accept));
+        }
+        Action::TokenizeFile {
+            path,
+            format,
+            cleaning_mode,
+        } => {
+            return Ok(run_tokenize_file_subcommand(
+                &path,
+                edition,
+                format,
+                cleaning_mode,
+            ));
+        }
     }
 
-    Ok(())
+    Ok(0)
 }
@@ -1,13 +1,20 @@
 //! High-level support for comparing the rustc and lexclucid analyses.
 
+use std::cell::RefCell;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
 use crate::cleaning;
 use crate::combination;
 use crate::lex_via_rustc;
-use crate::lexlucid;
-use crate::regular_tokens::{regularise_from_coarse, regularise_from_rustc, RegularToken};
+use crate::lexlucid::{self, RejectionCategory};
+use crate::regular_tokens::{
+    regularise_from_coarse, regularise_from_rustc, RegularToken, RegularTokenData,
+};
 use crate::Edition;
 
 /// The "regularised" result of running a lexer.
+#[derive(Clone)]
 pub enum Regularisation {
     /// The lexer accepted the input.
     ///
@@ -16,8 +23,11 @@ pub enum Regularisation {
 
     /// The lexer rejected the input.
     ///
+    /// The category is None if the reason for rejection couldn't be categorised (see
+    /// [`categorise_rustc_rejection`]).
+    ///
     /// The strings describe why the input was rejected.
-    Rejects(Vec<String>),
+    Rejects(Option<RejectionCategory>, Vec<String>),
 
     /// The lexer reported a problem in its model or implementation.
     ModelError(Vec<String>),
@@ -28,20 +38,304 @@ pub fn regularised_from_rustc(input: &str, edition: Edition) -> Regularisation {
     use lex_via_rustc::Analysis::*;
     match lex_via_rustc::analyse(input, edition) {
         Accepts(tokens) => Regularisation::Accepts(regularise_from_rustc(tokens)),
-        Rejects(_, messages) => Regularisation::Rejects(messages),
+        Rejects(_, messages) => {
+            Regularisation::Rejects(categorise_rustc_rejection(&messages), messages)
+        }
         CompilerError => Regularisation::ModelError(vec!["rustc compiler error".into()]),
     }
 }
 
 /// Run lexlucid's lexical analysis and return the regularised result.
+///
+/// Reports a model error if the analysis takes longer than [`ANALYSIS_TIMEOUT`].
 pub fn regularised_from_lexlucid(input: &str, edition: Edition) -> Regularisation {
-    use lexlucid::Analysis::*;
     let cleaned = cleaning::clean(input);
-    match lexlucid::analyse(&cleaned, edition) {
+    match with_timeout(move || lexlucid::analyse(&cleaned, edition)) {
+        Some(analysis) => regularise_lexlucid_analysis(analysis),
+        None => timed_out(),
+    }
+}
+
+/// How long a single lexlucid analysis may take before it's reported as a model error.
+pub const ANALYSIS_TIMEOUT: Duration = Duration::from_secs(10);
+
+type AnalysisJob = Box<dyn FnOnce() + Send>;
+
+thread_local! {
+    /// Sends jobs to this thread's analysis worker (see [`with_timeout`]), if it has one.
+    static ANALYSIS_WORKER: RefCell<Option<mpsc::Sender<AnalysisJob>>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` on this thread's analysis worker, returning None if it doesn't finish within
+/// [`ANALYSIS_TIMEOUT`].
+///
+/// Each thread which calls this gets a single long-lived worker thread. There's no way to stop a
+/// worker, so if it times out it's abandoned (it exits once it finishes the analysis) and the next
+/// call starts a new one.
+///
+/// If `f` panics, this panics too.
+fn with_timeout<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> Option<T> {
+    let (sender, receiver) = mpsc::channel();
+    let job: AnalysisJob = Box::new(move || {
+        // The receiver will have gone away if we timed out
+        let _ = sender.send(f());
+    });
+    ANALYSIS_WORKER.with_borrow_mut(|worker| {
+        worker
+            .get_or_insert_with(spawn_analysis_worker)
+            .send(job)
+            .expect("analysis worker exited")
+    });
+    match receiver.recv_timeout(ANALYSIS_TIMEOUT) {
+        Ok(result) => Some(result),
+        Err(error) => {
+            // Dropping the worker's sender makes it exit when it's next idle
+            ANALYSIS_WORKER.with_borrow_mut(|worker| *worker = None);
+            match error {
+                mpsc::RecvTimeoutError::Timeout => None,
+                mpsc::RecvTimeoutError::Disconnected => panic!("lexlucid's analysis panicked"),
+            }
+        }
+    }
+}
+
+fn spawn_analysis_worker() -> mpsc::Sender<AnalysisJob> {
+    let (sender, receiver) = mpsc::channel::<AnalysisJob>();
+    std::thread::Builder::new()
+        .name("lexlucid".into())
+        .spawn(move || {
+            for job in receiver {
+                job();
+            }
+        })
+        .expect("failed to spawn analysis thread");
+    sender
+}
+
+fn timed_out() -> Regularisation {
+    Regularisation::ModelError(vec![format!(
+        "lexlucid's analysis took longer than {}s",
+        ANALYSIS_TIMEOUT.as_secs()
+    )])
+}
+
+/// Makes a best-effort attempt to categorise rustc's reason for rejecting an input, using its
+/// error messages.
+///
+/// Each message is compared with the diagnostics which the lexer in the pinned version of rustc
+/// can emit (see [`RUSTC_DIAGNOSTICS`]). The first message which matches decides the category.
+///
+/// Returns None if no message is recognised.
+pub fn categorise_rustc_rejection(messages: &[String]) -> Option<RejectionCategory> {
+    messages.iter().find_map(|message| {
+        RUSTC_DIAGNOSTICS
+            .iter()
+            .find(|(pattern, _)| diagnostic_matches(message, pattern))
+            .map(|(_, category)| *category)
+    })
+}
+
+/// The rustc lexer's error diagnostics, with the category of rejection each one represents.
+///
+/// `lex_via_rustc` records diagnostics which rustc defines in its Fluent files by their Fluent
+/// identifiers, and others by their text. Diagnostics of the first kind are listed in both forms.
+///
+/// `{}` in a pattern stands for an interpolated value.
+///
+/// Some diagnostics are deliberately missing because lexlucid doesn't put all the inputs they're
+/// reported for in the same category:
+///  - `parse_bare_cr` is used for a CR in a string and for a CR in a character literal
+///  - `parse_invalid_char_in_escape` is used for both hexadecimal and unicode escapes
+///  - `parse_more_than_one_char` is used for `'ab'` and for `'\n\n'`
+pub(crate) const RUSTC_DIAGNOSTICS: &[(&str, RejectionCategory)] = {
+    use RejectionCategory::*;
+    &[
+        ("parse_unknown_start_of_token", NoRuleMatched),
+        ("unknown start of token: {}", NoRuleMatched),
+        ("unterminated double quote string", NoRuleMatched),
+        ("unterminated character literal", NoRuleMatched),
+        ("parse_zero_chars", NoRuleMatched),
+        ("empty character literal", NoRuleMatched),
+        ("unterminated double quote byte string", ReservedForm),
+        ("unterminated C string", ReservedForm),
+        ("unterminated byte constant", ReservedForm),
+        ("unterminated raw string", ReservedForm),
+        ("unterminated block comment", ReservedForm),
+        ("unterminated block doc-comment", ReservedForm),
+        (
+            "found invalid character; only `#` is allowed in raw string delimitation: {}",
+            ReservedForm,
+        ),
+        ("parse_too_many_hashes", ReservedForm),
+        (
+            "too many `#` symbols: raw strings may be delimited by up to 255 `#` symbols, but found {}",
+            ReservedForm,
+        ),
+        ("parse_unknown_prefix", ReservedForm),
+        ("prefix `{}` is unknown", ReservedForm),
+        ("parse_reserved_string", ReservedForm),
+        ("invalid string literal", ReservedForm),
+        ("parse_reserved_multihash", ReservedForm),
+        ("reserved multi-hash token is forbidden", ReservedForm),
+        ("bare CR not allowed in string, use `\\r` instead", BareCr),
+        ("parse_bare_cr_in_raw_string", BareCr),
+        ("bare CR not allowed in raw string", BareCr),
+        ("parse_cr_doc_comment", BareCr),
+        ("bare CR not allowed in doc-comment", BareCr),
+        ("bare CR not allowed in block doc-comment", BareCr),
+        ("parse_nul_in_c_str", NulInCString),
+        (
+            "null characters in C string literals are not supported",
+            NulInCString,
+        ),
+        ("parse_escape_only_char", EscapeOnlyChar),
+        ("character constant must be escaped: `{}`", EscapeOnlyChar),
+        ("byte constant must be escaped: `{}`", EscapeOnlyChar),
+        ("unknown character escape: `{}`", UnknownEscape),
+        ("unknown byte escape: `{}`", UnknownEscape),
+        ("parse_unicode_escape_in_byte", UnknownEscape),
+        ("unicode escape in byte string", UnknownEscape),
+        ("parse_too_short_hex_escape", InvalidHexEscape),
+        ("numeric character escape is too short", InvalidHexEscape),
+        ("invalid character in numeric character escape: `{}`", InvalidHexEscape),
+        ("parse_out_of_range_hex_escape", InvalidHexEscape),
+        ("out of range hex escape", InvalidHexEscape),
+        ("parse_invalid_unicode_escape", InvalidUnicodeEscape),
+        ("invalid unicode character escape", InvalidUnicodeEscape),
+        ("parse_no_brace_unicode_escape", InvalidUnicodeEscape),
+        ("incorrect unicode escape sequence", InvalidUnicodeEscape),
+        ("invalid character in unicode escape: `{}`", InvalidUnicodeEscape),
+        ("parse_empty_unicode_escape", InvalidUnicodeEscape),
+        ("empty unicode escape", InvalidUnicodeEscape),
+        ("parse_unclosed_unicode_escape", InvalidUnicodeEscape),
+        ("unterminated unicode escape", InvalidUnicodeEscape),
+        ("parse_leading_underscore_unicode_escape", InvalidUnicodeEscape),
+        ("invalid start of unicode escape: `_`", InvalidUnicodeEscape),
+        ("parse_overlong_unicode_escape", InvalidUnicodeEscape),
+        ("overlong unicode escape", InvalidUnicodeEscape),
+        ("non-ASCII character in byte literal", NonAscii),
+        ("non-ASCII character in byte string literal", NonAscii),
+        ("non-ASCII character in raw byte string literal", NonAscii),
+        ("parse_cannot_be_raw_ident", ForbiddenRawIdentifier),
+        ("`{}` cannot be a raw identifier", ForbiddenRawIdentifier),
+        ("parse_cannot_be_raw_lifetime", ForbiddenRawIdentifier),
+        ("`{}` cannot be a raw lifetime", ForbiddenRawIdentifier),
+        ("parse_underscore_literal_suffix", UnderscoreSuffix),
+        ("underscore literal suffix is not allowed", UnderscoreSuffix),
+        ("parse_no_digits_literal", NoDigits),
+        ("no valid digits found for number", NoDigits),
+        ("parse_empty_exponent_float", NoDigits),
+        ("expected at least one digit in exponent", NoDigits),
+        ("parse_invalid_digit_literal", InvalidDigit),
+        ("invalid digit for a base {} literal", InvalidDigit),
+        ("parse_float_literal_unsupported_base", UnsupportedFloatBase),
+        ("{} float literal is not supported", UnsupportedFloatBase),
+    ]
+};
+
+/// Says whether a diagnostic message matches a pattern from [`RUSTC_DIAGNOSTICS`].
+fn diagnostic_matches(message: &str, pattern: &str) -> bool {
+    let mut fixed_parts = pattern.split("{}");
+    let Some(mut rest) = message.strip_prefix(fixed_parts.next().unwrap()) else {
+        return false;
+    };
+    let fixed_parts: Vec<&str> = fixed_parts.collect();
+    let Some((last, middle)) = fixed_parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        let Some(index) = rest.find(part) else {
+            return false;
+        };
+        rest = &rest[index + part.len()..];
+    }
+    rest.len() > last.len() && rest.ends_with(last)
+}
+
+/// Says whether both regularisations are rejections whose (known) categories differ.
+pub fn rejection_categories_differ(r1: &Regularisation, r2: &Regularisation) -> bool {
+    matches!(
+        (r1, r2),
+        (Regularisation::Rejects(Some(c1), _), Regularisation::Rejects(Some(c2), _)) if c1 != c2
+    )
+}
+
+/// Time spent processing a single input.
+#[derive(Default)]
+pub struct InputTimings {
+    /// Time spent in input cleaning (see the `cleaning` module)
+    pub cleaning: Duration,
+    /// Time spent in lexlucid's step 1 (pretokenisation)
+    pub pretokenisation: Duration,
+    /// Time spent in lexlucid's step 2 (reprocessing)
+    pub reprocessing: Duration,
+    /// Time spent running rustc's lexical analysis (if it was run)
+    pub rustc: Option<Duration>,
+}
+
+impl InputTimings {
+    /// Returns the total time spent in lexlucid's processing (including cleaning).
+    pub fn lexlucid_total(&self) -> Duration {
+        self.cleaning + self.pretokenisation + self.reprocessing
+    }
+
+    /// Describes the timings on a single line.
+    pub fn describe(&self) -> String {
+        let mut description = format!(
+            "cleaning: {:?}, pretokenisation: {:?}, reprocessing: {:?}",
+            self.cleaning, self.pretokenisation, self.reprocessing
+        );
+        if let Some(rustc) = self.rustc {
+            description.push_str(&format!(", rustc: {:?}", rustc));
+        }
+        description
+    }
+}
+
+/// Run rustc's lexical analysis and return the regularised result, recording how long it took.
+pub fn regularised_from_rustc_timed(
+    input: &str,
+    edition: Edition,
+    timings: &mut InputTimings,
+) -> Regularisation {
+    let start = Instant::now();
+    let regularisation = regularised_from_rustc(input, edition);
+    timings.rustc = Some(start.elapsed());
+    regularisation
+}
+
+/// Run lexlucid's lexical analysis and return the regularised result, recording how long each
+/// stage took.
+///
+/// Reports a model error if the analysis takes longer than [`ANALYSIS_TIMEOUT`].
+pub fn regularised_from_lexlucid_timed(
+    input: &str,
+    edition: Edition,
+    timings: &mut InputTimings,
+) -> Regularisation {
+    let start = Instant::now();
+    let cleaned = cleaning::clean(input);
+    timings.cleaning = start.elapsed();
+    let Some((analysis, step_timings)) =
+        with_timeout(move || lexlucid::analyse_timed(&cleaned, edition))
+    else {
+        return timed_out();
+    };
+    timings.pretokenisation = step_timings.pretokenisation;
+    timings.reprocessing = step_timings.reprocessing;
+    regularise_lexlucid_analysis(analysis)
+}
+
+/// Converts the result of lexlucid's analysis to a `Regularisation`.
+pub fn regularise_lexlucid_analysis(analysis: lexlucid::Analysis) -> Regularisation {
+    use lexlucid::Analysis::*;
+    match analysis {
         Accepts(_, fine_tokens) => {
             Regularisation::Accepts(regularise_from_coarse(combination::coarsen(fine_tokens)))
         }
-        Rejects(reason) => Regularisation::Rejects(reason.into_description()),
+        Rejects(category, reason) => {
+            Regularisation::Rejects(Some(category), reason.into_description())
+        }
         ModelError(reason) => Regularisation::ModelError(reason.into_description()),
     }
 }
@@ -74,9 +368,279 @@ pub fn compare(r1: &Regularisation, r2: &Regularisation) -> Comparison {
     match (r1, r2) {
         (Accepts(tokens1), Accepts(tokens2)) if tokens1 == tokens2 => Agree,
         (Accepts(_), Accepts(_)) => Differ,
-        (Rejects(_), Rejects(_)) => Agree,
-        (Accepts(_), Rejects(_)) => Differ,
-        (Rejects(_), Accepts(_)) => Differ,
+        (Rejects(..), Rejects(..)) => Agree,
+        (Accepts(_), Rejects(..)) => Differ,
+        (Rejects(..), Accepts(_)) => Differ,
         _ => ModelErrors,
     }
 }
+
+/// A detailed description of the result of comparing the output of two lexers.
+pub struct ComparisonReport {
+    /// The result of the comparison.
+    pub comparison: Comparison,
+    /// Human-readable description of both lexers' output, and of how they differ.
+    pub lines: Vec<String>,
+}
+
+/// Compare the output of two lexers, and describe the result.
+///
+/// `left_name` and `right_name` are used to label each lexer's output in the report.
+///
+/// If both lexers accepted the input but produced different tokens, the report shows a diff of the
+/// two token sequences (see [`diff_token_streams`]) rather than listing each of them in full. It
+/// also says where the first difference is, shows the token before it for context, and says
+/// whether the tokens differ only in their spacing.
+pub fn report_token_stream_comparison(
+    left_name: &str,
+    left: &Regularisation,
+    right_name: &str,
+    right: &Regularisation,
+) -> ComparisonReport {
+    let comparison = compare(left, right);
+    let mut lines = Vec::new();
+    use Regularisation::*;
+    match (left, right) {
+        (Accepts(left_tokens), Accepts(right_tokens)) if left_tokens != right_tokens => {
+            lines.push(format!("{left_name}: accepted"));
+            lines.push(format!("{right_name}: accepted"));
+            describe_diff(
+                (left_name, left_tokens),
+                (right_name, right_tokens),
+                &mut lines,
+            );
+        }
+        _ => {
+            describe_regularisation(left_name, left, &mut lines);
+            describe_regularisation(right_name, right, &mut lines);
+        }
+    }
+    match (left, right) {
+        (Accepts(left_tokens), Accepts(right_tokens)) => {
+            if let Some(index) = first_difference(left_tokens, right_tokens) {
+                describe_difference(
+                    index,
+                    (left_name, left_tokens),
+                    (right_name, right_tokens),
+                    &mut lines,
+                );
+            }
+        }
+        (Accepts(_), Rejects(..)) => {
+            lines.push(format!("{left_name} accepted but {right_name} rejected"))
+        }
+        (Rejects(..), Accepts(_)) => {
+            lines.push(format!("{right_name} accepted but {left_name} rejected"))
+        }
+        _ => {}
+    }
+    ComparisonReport { comparison, lines }
+}
+
+fn describe_regularisation(name: &str, regularisation: &Regularisation, lines: &mut Vec<String>) {
+    match regularisation {
+        Regularisation::Accepts(tokens) => {
+            lines.push(format!("{name}: accepted"));
+            for token in tokens {
+                lines.push(format!("  {:?}", token));
+            }
+        }
+        Regularisation::Rejects(category, messages) => {
+            lines.push(format!("{name}: rejected"));
+            for msg in messages {
+                lines.push(format!("  {msg}"));
+            }
+            if let Some(category) = category {
+                lines.push(format!("  category: {category:?}"));
+            }
+        }
+        Regularisation::ModelError(messages) => {
+            lines.push(format!("{name}: reported a model error"));
+            for msg in messages {
+                lines.push(format!("  {msg}"));
+            }
+        }
+    }
+}
+
+/// Number of unchanged tokens to show on each side of a change in a diff.
+const DIFF_CONTEXT: usize = 2;
+
+fn describe_diff(
+    (left_name, left): (&str, &[RegularToken]),
+    (right_name, right): (&str, &[RegularToken]),
+    lines: &mut Vec<String>,
+) {
+    let Some(diff) = diff_token_streams(left, right) else {
+        lines.push(format!(
+            "({left_name} and {right_name} differ in too many tokens to show a diff)"
+        ));
+        return;
+    };
+    lines.push(format!("diff (-{left_name} +{right_name}):"));
+    let is_near_change = |index: usize| {
+        diff[index.saturating_sub(DIFF_CONTEXT)..(index + DIFF_CONTEXT + 1).min(diff.len())]
+            .iter()
+            .any(|line| !matches!(line, DiffLine::Both(_)))
+    };
+    let mut elided = 0;
+    for (index, line) in diff.iter().enumerate() {
+        if !is_near_change(index) {
+            elided += 1;
+            continue;
+        }
+        if elided != 0 {
+            lines.push(format!("  ... {elided} unchanged"));
+            elided = 0;
+        }
+        lines.push(match line {
+            DiffLine::Both(token) => format!("    {:?}", token),
+            DiffLine::LeftOnly(token) => format!("  - {:?}", token),
+            DiffLine::RightOnly(token) => format!("  + {:?}", token),
+        });
+    }
+    if elided != 0 {
+        lines.push(format!("  ... {elided} unchanged"));
+    }
+}
+
+/// One line of a diff between two token sequences.
+#[derive(PartialEq, Eq, Debug)]
+pub enum DiffLine<'a> {
+    /// A token which appears in both sequences.
+    Both(&'a RegularToken),
+    /// A token which appears only in the left-hand sequence.
+    LeftOnly(&'a RegularToken),
+    /// A token which appears only in the right-hand sequence.
+    RightOnly(&'a RegularToken),
+}
+
+/// Largest number of entries in the table [`diff_token_streams`] will build to find a longest
+/// common subsequence.
+const MAX_DIFF_TABLE_SIZE: usize = 4_000_000;
+
+/// Computes a diff between two token sequences, based on their longest common subsequence.
+///
+/// Where tokens are removed and added at the same place, the removals are listed first.
+///
+/// Returns None if the part of the sequences between their common prefix and common suffix is too
+/// long to diff (see [`MAX_DIFF_TABLE_SIZE`]).
+pub fn diff_token_streams<'a>(
+    left: &'a [RegularToken],
+    right: &'a [RegularToken],
+) -> Option<Vec<DiffLine<'a>>> {
+    let prefix_len = left.iter().zip(right).take_while(|(l, r)| l == r).count();
+    let suffix_len = left[prefix_len..]
+        .iter()
+        .rev()
+        .zip(right[prefix_len..].iter().rev())
+        .take_while(|(l, r)| l == r)
+        .count();
+    let left_middle = &left[prefix_len..left.len() - suffix_len];
+    let right_middle = &right[prefix_len..right.len() - suffix_len];
+    if (left_middle.len() + 1).saturating_mul(right_middle.len() + 1) > MAX_DIFF_TABLE_SIZE {
+        return None;
+    }
+    let mut diff: Vec<_> = left[..prefix_len].iter().map(DiffLine::Both).collect();
+    diff.extend(diff_by_lcs_table(left_middle, right_middle));
+    diff.extend(left[left.len() - suffix_len..].iter().map(DiffLine::Both));
+    Some(diff)
+}
+
+/// Computes a diff between two token sequences using a table of the lengths of longest common
+/// subsequences, which has an entry for each pair of positions in the sequences.
+fn diff_by_lcs_table<'a>(left: &'a [RegularToken], right: &'a [RegularToken]) -> Vec<DiffLine<'a>> {
+    // lcs[i][j] is the length of the longest common subsequence of left[i..] and right[j..]
+    let mut lcs = vec![vec![0; right.len() + 1]; left.len() + 1];
+    for i in (0..left.len()).rev() {
+        for j in (0..right.len()).rev() {
+            lcs[i][j] = if left[i] == right[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < left.len() && j < right.len() {
+        if left[i] == right[j] {
+            diff.push(DiffLine::Both(&left[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.push(DiffLine::LeftOnly(&left[i]));
+            i += 1;
+        } else {
+            diff.push(DiffLine::RightOnly(&right[j]));
+            j += 1;
+        }
+    }
+    diff.extend(left[i..].iter().map(DiffLine::LeftOnly));
+    diff.extend(right[j..].iter().map(DiffLine::RightOnly));
+    diff
+}
+
+/// Returns the index of the first token which differs between the two sequences.
+///
+/// If one sequence is a prefix of the other, returns the length of the shorter sequence.
+///
+/// Returns None if the sequences are equal.
+fn first_difference(left: &[RegularToken], right: &[RegularToken]) -> Option<usize> {
+    match left.iter().zip(right).position(|(l, r)| l != r) {
+        Some(index) => Some(index),
+        None if left.len() != right.len() => Some(left.len().min(right.len())),
+        None => None,
+    }
+}
+
+fn describe_difference(
+    index: usize,
+    (left_name, left): (&str, &[RegularToken]),
+    (right_name, right): (&str, &[RegularToken]),
+    lines: &mut Vec<String>,
+) {
+    lines.push(format!("first difference at token {index}"));
+    if index > 0 {
+        lines.push(format!("  after: {:?}", left[index - 1]));
+    }
+    let describe_token = |token: Option<&RegularToken>| match token {
+        Some(token) => format!("{:?}", token),
+        None => "end of input".to_owned(),
+    };
+    lines.push(format!(
+        "  {left_name}: {}",
+        describe_token(left.get(index))
+    ));
+    lines.push(format!(
+        "  {right_name}: {}",
+        describe_token(right.get(index))
+    ));
+    if let (Some(l), Some(r)) = (left.get(index), right.get(index)) {
+        if l.extent == r.extent && l.data == r.data {
+            lines.push(format!(
+                "  tokens differ only in spacing after {:?}",
+                l.extent
+            ));
+        }
+        // Both lexers are expected to normalise identifiers, so this is still a difference
+        if let (
+            RegularTokenData::Identifier {
+                represented_identifier: id1,
+                style: style1,
+            },
+            RegularTokenData::Identifier {
+                represented_identifier: id2,
+                style: style2,
+            },
+        ) = (&l.data, &r.data)
+        {
+            if style1 == style2 && id1 != id2 && id1.nfc_eq(id2) {
+                lines.push("  identifiers differ only in normalisation".to_owned());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
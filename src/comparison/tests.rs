@@ -0,0 +1,404 @@
+use crate::char_sequences::Charseq;
+use crate::lexlucid::RejectionCategory;
+use crate::regular_tokens::{IdentifierStyle, RegularToken, RegularTokenData, Spacing};
+use crate::Edition;
+
+use super::{
+    categorise_rustc_rejection, diagnostic_matches, diff_token_streams, regularised_from_lexlucid,
+    rejection_categories_differ, report_token_stream_comparison, Comparison, DiffLine,
+    Regularisation, RUSTC_DIAGNOSTICS,
+};
+
+fn punctuation(s: &str, spacing: Spacing) -> RegularToken {
+    RegularToken {
+        extent: s.into(),
+        spacing,
+        data: RegularTokenData::Punctuation,
+    }
+}
+
+fn identifier(s: &str) -> RegularToken {
+    RegularToken {
+        extent: s.into(),
+        spacing: Spacing::Alone,
+        data: RegularTokenData::Identifier {
+            represented_identifier: Charseq::from(s),
+            style: IdentifierStyle::NonRaw,
+        },
+    }
+}
+
+fn report(left: &Regularisation, right: &Regularisation) -> (Comparison, Vec<String>) {
+    let report = report_token_stream_comparison("left", left, "right", right);
+    (report.comparison, report.lines)
+}
+
+#[test]
+fn equal_streams() {
+    let left = Regularisation::Accepts(vec![identifier("a"), punctuation("+", Spacing::Alone)]);
+    let right = Regularisation::Accepts(vec![identifier("a"), punctuation("+", Spacing::Alone)]);
+    let (comparison, lines) = report(&left, &right);
+    assert!(matches!(comparison, Comparison::Agree));
+    assert_eq!(lines[0], "left: accepted");
+    assert_eq!(lines[3], "right: accepted");
+    assert_eq!(lines.len(), 6);
+}
+
+#[test]
+fn first_difference() {
+    let left = Regularisation::Accepts(vec![identifier("a"), identifier("b"), identifier("c")]);
+    let right = Regularisation::Accepts(vec![identifier("a"), identifier("x"), identifier("c")]);
+    let (comparison, lines) = report(&left, &right);
+    assert!(matches!(comparison, Comparison::Differ));
+    let position = lines
+        .iter()
+        .position(|line| line == "first difference at token 1")
+        .unwrap();
+    assert!(lines[position + 1].starts_with("  after: extent: «a» "));
+    assert!(lines[position + 2].starts_with("  left: extent: «b» "));
+    assert!(lines[position + 3].starts_with("  right: extent: «x» "));
+    assert_eq!(lines.len(), position + 4);
+}
+
+#[test]
+fn difference_at_end_of_input() {
+    let left = Regularisation::Accepts(vec![identifier("a")]);
+    let right = Regularisation::Accepts(vec![identifier("a"), identifier("b")]);
+    let (comparison, lines) = report(&left, &right);
+    assert!(matches!(comparison, Comparison::Differ));
+    assert!(lines.contains(&"first difference at token 1".to_owned()));
+    assert!(lines.contains(&"  left: end of input".to_owned()));
+}
+
+#[test]
+fn spacing_only_difference() {
+    let left = Regularisation::Accepts(vec![punctuation("+", Spacing::Joint)]);
+    let right = Regularisation::Accepts(vec![punctuation("+", Spacing::Alone)]);
+    let (comparison, lines) = report(&left, &right);
+    assert!(matches!(comparison, Comparison::Differ));
+    assert!(lines.contains(&"first difference at token 0".to_owned()));
+    assert!(!lines.iter().any(|line| line.starts_with("  after:")));
+    assert_eq!(
+        lines.last().unwrap(),
+        "  tokens differ only in spacing after «+»"
+    );
+}
+
+#[test]
+fn acceptance_direction() {
+    let accepts = Regularisation::Accepts(vec![identifier("a")]);
+    let rejects = Regularisation::Rejects(None, vec!["bad input".into()]);
+    let (comparison, lines) = report(&accepts, &rejects);
+    assert!(matches!(comparison, Comparison::Differ));
+    assert_eq!(lines.last().unwrap(), "left accepted but right rejected");
+    assert!(lines.contains(&"right: rejected".to_owned()));
+    assert!(lines.contains(&"  bad input".to_owned()));
+    let (_, lines) = report(&rejects, &accepts);
+    assert_eq!(lines.last().unwrap(), "right accepted but left rejected");
+}
+
+#[test]
+fn both_reject() {
+    let left = Regularisation::Rejects(None, vec!["one reason".into()]);
+    let right = Regularisation::Rejects(None, vec!["another reason".into()]);
+    let (comparison, lines) = report(&left, &right);
+    assert!(matches!(comparison, Comparison::Agree));
+    assert_eq!(
+        lines,
+        vec![
+            "left: rejected",
+            "  one reason",
+            "right: rejected",
+            "  another reason"
+        ]
+    );
+}
+
+#[test]
+fn model_error() {
+    let left = Regularisation::Accepts(vec![identifier("a")]);
+    let right = Regularisation::ModelError(vec!["oops".into()]);
+    let (comparison, lines) = report(&left, &right);
+    assert!(matches!(comparison, Comparison::ModelErrors));
+    assert_eq!(lines.last().unwrap(), "  oops");
+    assert!(lines.contains(&"right: reported a model error".to_owned()));
+}
+
+#[test]
+fn diff_replacement() {
+    let left = vec![identifier("a"), identifier("b"), identifier("c")];
+    let right = vec![identifier("a"), identifier("x"), identifier("c")];
+    assert_eq!(
+        diff_token_streams(&left, &right).unwrap(),
+        vec![
+            DiffLine::Both(&left[0]),
+            DiffLine::LeftOnly(&left[1]),
+            DiffLine::RightOnly(&right[1]),
+            DiffLine::Both(&left[2]),
+        ]
+    );
+}
+
+#[test]
+fn diff_insertion_and_deletion() {
+    let left = vec![identifier("a"), identifier("b"), identifier("c")];
+    let right = vec![identifier("b"), identifier("c"), identifier("d")];
+    assert_eq!(
+        diff_token_streams(&left, &right).unwrap(),
+        vec![
+            DiffLine::LeftOnly(&left[0]),
+            DiffLine::Both(&left[1]),
+            DiffLine::Both(&left[2]),
+            DiffLine::RightOnly(&right[2]),
+        ]
+    );
+}
+
+#[test]
+fn diff_of_long_streams_differing_in_one_token() {
+    let left: Vec<_> = (0..50_000).map(|i| identifier(&format!("t{i}"))).collect();
+    let mut right: Vec<_> = (0..50_000).map(|i| identifier(&format!("t{i}"))).collect();
+    right[30_000] = identifier("x");
+    let diff = diff_token_streams(&left, &right).unwrap();
+    assert_eq!(diff.len(), 50_001);
+    assert_eq!(diff[29_999], DiffLine::Both(&left[29_999]));
+    assert_eq!(diff[30_000], DiffLine::LeftOnly(&left[30_000]));
+    assert_eq!(diff[30_001], DiffLine::RightOnly(&right[30_000]));
+    assert_eq!(diff[30_002], DiffLine::Both(&left[30_001]));
+    assert_eq!(diff[50_000], DiffLine::Both(&left[49_999]));
+}
+
+#[test]
+fn diff_of_long_streams_differing_throughout_falls_back() {
+    let left: Vec<_> = (0..5_000).map(|i| identifier(&format!("l{i}"))).collect();
+    let right: Vec<_> = (0..5_000).map(|i| identifier(&format!("r{i}"))).collect();
+    assert_eq!(diff_token_streams(&left, &right), None);
+    let (_, lines) = report(
+        &Regularisation::Accepts(left),
+        &Regularisation::Accepts(right),
+    );
+    assert!(lines.contains(&"(left and right differ in too many tokens to show a diff)".to_owned()));
+    assert!(lines.contains(&"first difference at token 0".to_owned()));
+}
+
+#[test]
+fn diff_in_report_elides_unchanged_tokens() {
+    let names = ["a", "b", "c", "d", "e", "f", "g", "h"];
+    let left = Regularisation::Accepts(names.iter().map(|s| identifier(s)).collect());
+    let right = Regularisation::Accepts(
+        names
+            .iter()
+            .map(|s| identifier(if *s == "f" { "x" } else { s }))
+            .collect(),
+    );
+    let (_, lines) = report(&left, &right);
+    let diff: Vec<_> = lines
+        .iter()
+        .skip_while(|line| !line.starts_with("diff"))
+        .take_while(|line| !line.starts_with("first difference"))
+        .collect();
+    assert_eq!(diff[0], "diff (-left +right):");
+    assert_eq!(diff[1], "  ... 3 unchanged");
+    assert!(diff[2].starts_with("    extent: «d» "));
+    assert!(diff[4].starts_with("  - extent: «f» "));
+    assert!(diff[5].starts_with("  + extent: «x» "));
+    assert!(diff[7].starts_with("    extent: «h» "));
+    assert_eq!(diff.len(), 8);
+}
+
+#[test]
+fn rejection_categories() {
+    use crate::lexlucid::RejectionCategory;
+    let bare_cr = Regularisation::Rejects(Some(RejectionCategory::BareCr), vec!["CR".into()]);
+    let unknown_escape = Regularisation::Rejects(
+        Some(RejectionCategory::UnknownEscape),
+        vec!["escape".into()],
+    );
+    let uncategorised = Regularisation::Rejects(None, vec!["?".into()]);
+    assert!(rejection_categories_differ(&bare_cr, &unknown_escape));
+    assert!(!rejection_categories_differ(&bare_cr, &bare_cr));
+    assert!(!rejection_categories_differ(&bare_cr, &uncategorised));
+    let (comparison, lines) = report(&bare_cr, &unknown_escape);
+    assert!(matches!(comparison, Comparison::Agree));
+    assert!(lines.contains(&"  category: BareCr".to_owned()));
+}
+
+/// An example message for each pattern in RUSTC_DIAGNOSTICS, with the expected category.
+const RUSTC_DIAGNOSTIC_EXAMPLES: &[(&str, RejectionCategory)] = {
+    use RejectionCategory::*;
+    &[
+        ("parse_unknown_start_of_token", NoRuleMatched),
+        ("unknown start of token: \\u{1f980}", NoRuleMatched),
+        ("unterminated double quote string", NoRuleMatched),
+        ("unterminated character literal", NoRuleMatched),
+        ("parse_zero_chars", NoRuleMatched),
+        ("empty character literal", NoRuleMatched),
+        ("unterminated double quote byte string", ReservedForm),
+        ("unterminated C string", ReservedForm),
+        ("unterminated byte constant", ReservedForm),
+        ("unterminated raw string", ReservedForm),
+        ("unterminated block comment", ReservedForm),
+        ("unterminated block doc-comment", ReservedForm),
+        (
+            "found invalid character; only `#` is allowed in raw string delimitation: x",
+            ReservedForm,
+        ),
+        ("parse_too_many_hashes", ReservedForm),
+        (
+            "too many `#` symbols: raw strings may be delimited by up to 255 `#` symbols, but found 256",
+            ReservedForm,
+        ),
+        ("parse_unknown_prefix", ReservedForm),
+        ("prefix `foo` is unknown", ReservedForm),
+        ("parse_reserved_string", ReservedForm),
+        ("invalid string literal", ReservedForm),
+        ("parse_reserved_multihash", ReservedForm),
+        ("reserved multi-hash token is forbidden", ReservedForm),
+        ("bare CR not allowed in string, use `\\r` instead", BareCr),
+        ("parse_bare_cr_in_raw_string", BareCr),
+        ("bare CR not allowed in raw string", BareCr),
+        ("parse_cr_doc_comment", BareCr),
+        ("bare CR not allowed in doc-comment", BareCr),
+        ("bare CR not allowed in block doc-comment", BareCr),
+        ("parse_nul_in_c_str", NulInCString),
+        (
+            "null characters in C string literals are not supported",
+            NulInCString,
+        ),
+        ("parse_escape_only_char", EscapeOnlyChar),
+        ("character constant must be escaped: `\\n`", EscapeOnlyChar),
+        ("byte constant must be escaped: `\\t`", EscapeOnlyChar),
+        ("unknown character escape: `q`", UnknownEscape),
+        ("unknown byte escape: `q`", UnknownEscape),
+        ("parse_unicode_escape_in_byte", UnknownEscape),
+        ("unicode escape in byte string", UnknownEscape),
+        ("parse_too_short_hex_escape", InvalidHexEscape),
+        ("numeric character escape is too short", InvalidHexEscape),
+        ("invalid character in numeric character escape: `z`", InvalidHexEscape),
+        ("parse_out_of_range_hex_escape", InvalidHexEscape),
+        ("out of range hex escape", InvalidHexEscape),
+        ("parse_invalid_unicode_escape", InvalidUnicodeEscape),
+        ("invalid unicode character escape", InvalidUnicodeEscape),
+        ("parse_no_brace_unicode_escape", InvalidUnicodeEscape),
+        ("incorrect unicode escape sequence", InvalidUnicodeEscape),
+        ("invalid character in unicode escape: `x`", InvalidUnicodeEscape),
+        ("parse_empty_unicode_escape", InvalidUnicodeEscape),
+        ("empty unicode escape", InvalidUnicodeEscape),
+        ("parse_unclosed_unicode_escape", InvalidUnicodeEscape),
+        ("unterminated unicode escape", InvalidUnicodeEscape),
+        ("parse_leading_underscore_unicode_escape", InvalidUnicodeEscape),
+        ("invalid start of unicode escape: `_`", InvalidUnicodeEscape),
+        ("parse_overlong_unicode_escape", InvalidUnicodeEscape),
+        ("overlong unicode escape", InvalidUnicodeEscape),
+        ("non-ASCII character in byte literal", NonAscii),
+        ("non-ASCII character in byte string literal", NonAscii),
+        ("non-ASCII character in raw byte string literal", NonAscii),
+        ("parse_cannot_be_raw_ident", ForbiddenRawIdentifier),
+        ("`self` cannot be a raw identifier", ForbiddenRawIdentifier),
+        ("parse_cannot_be_raw_lifetime", ForbiddenRawIdentifier),
+        ("`_` cannot be a raw lifetime", ForbiddenRawIdentifier),
+        ("parse_underscore_literal_suffix", UnderscoreSuffix),
+        ("underscore literal suffix is not allowed", UnderscoreSuffix),
+        ("parse_no_digits_literal", NoDigits),
+        ("no valid digits found for number", NoDigits),
+        ("parse_empty_exponent_float", NoDigits),
+        ("expected at least one digit in exponent", NoDigits),
+        ("parse_invalid_digit_literal", InvalidDigit),
+        ("invalid digit for a base 2 literal", InvalidDigit),
+        ("parse_float_literal_unsupported_base", UnsupportedFloatBase),
+        ("hexadecimal float literal is not supported", UnsupportedFloatBase),
+    ]
+};
+
+#[test]
+fn categorise_each_rustc_diagnostic() {
+    for (message, expected) in RUSTC_DIAGNOSTIC_EXAMPLES {
+        assert_eq!(
+            categorise_rustc_rejection(&[message.to_string()]),
+            Some(*expected),
+            "{message}"
+        );
+    }
+}
+
+#[test]
+fn every_rustc_diagnostic_pattern_has_an_example() {
+    for (pattern, _) in RUSTC_DIAGNOSTICS {
+        assert!(
+            RUSTC_DIAGNOSTIC_EXAMPLES
+                .iter()
+                .any(|(message, _)| diagnostic_matches(message, pattern)),
+            "{pattern}"
+        );
+    }
+}
+
+#[test]
+fn categorise_unrecognised_rustc_messages() {
+    for message in [
+        "something new",
+        "unterminated something new",
+        "code: E0765",
+        "reported fatal error (panicked)",
+        "parse_bare_cr",
+        "parse_invalid_char_in_escape",
+        "parse_more_than_one_char",
+        "prefix `foo` is unknown, really",
+        "invalid digit for a base  literal",
+    ] {
+        assert_eq!(
+            categorise_rustc_rejection(&[message.to_owned()]),
+            None,
+            "{message}"
+        );
+    }
+}
+
+#[test]
+fn categorise_uses_first_recognised_rustc_message() {
+    let messages = [
+        "code: E0765".to_owned(),
+        "unterminated double quote string".to_owned(),
+        "parse_unknown_prefix".to_owned(),
+    ];
+    assert_eq!(
+        categorise_rustc_rejection(&messages),
+        Some(RejectionCategory::NoRuleMatched)
+    );
+}
+
+#[test]
+fn normalisation_only_difference() {
+    let left = Regularisation::Accepts(vec![identifier("\u{e9}")]);
+    let right = Regularisation::Accepts(vec![identifier("e\u{301}")]);
+    let (comparison, lines) = report(&left, &right);
+    assert!(matches!(comparison, Comparison::Differ));
+    assert_eq!(
+        lines.last().unwrap(),
+        "  identifiers differ only in normalisation"
+    );
+    let (_, lines) = report(&left, &Regularisation::Accepts(vec![identifier("e")]));
+    assert!(!lines.contains(&"  identifiers differ only in normalisation".to_owned()));
+}
+
+#[test]
+fn regularised_c_strings_have_a_single_terminating_nul() {
+    for (input, expected) in [
+        (r#"c"""#, &b"\0"[..]),
+        (r#"cr"""#, b"\0"),
+        (r#"c"x""#, b"x\0"),
+        (r##"cr#"x"#"##, b"x\0"),
+    ] {
+        let Regularisation::Accepts(tokens) = regularised_from_lexlucid(input, Edition::E2021)
+        else {
+            panic!("{input} should be accepted");
+        };
+        let RegularTokenData::CstringLiteral {
+            represented_bytes, ..
+        } = &tokens[0].data
+        else {
+            panic!("{input} should be a C string literal");
+        };
+        assert_eq!(represented_bytes, expected, "{input}");
+    }
+}
@@ -0,0 +1,73 @@
+//! Reports identifiers which might be confused with one another.
+//!
+//! This uses the confusable detection and mixed-script detection from Unicode Technical Standard
+//! #39, as implemented by the `unicode-security` crate (which rustc also uses for its
+//! `confusable_idents` and `mixed_script_confusables` lints).
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use unicode_security::{skeleton, MixedScript};
+
+use crate::cleaning;
+use crate::lexlucid::{self, FineTokenData};
+use crate::utils::escape_for_display;
+use crate::Edition;
+
+/// Implements the `confusables` CLI command.
+///
+/// Lexes each input with lexlucid, and collects the (NFC-normalised) identifiers from all the
+/// inputs which were accepted. Then prints groups of distinct identifiers which have the same
+/// confusable skeleton, and identifiers which mix scripts.
+pub fn run_confusables_subcommand(inputs: &[&str], edition: Edition) {
+    let mut identifiers = BTreeSet::new();
+    for input in inputs {
+        let cleaned = cleaning::clean(input);
+        if let lexlucid::Analysis::Accepts(_, tokens) = lexlucid::analyse(&cleaned, edition) {
+            for token in tokens {
+                if let FineTokenData::Identifier {
+                    represented_identifier,
+                    ..
+                }
+                | FineTokenData::RawIdentifier {
+                    represented_identifier,
+                    ..
+                } = token.data
+                {
+                    identifiers.insert(represented_identifier.to_string());
+                }
+            }
+        }
+    }
+    println!("{} distinct identifiers", identifiers.len());
+
+    let mut by_skeleton: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    for identifier in identifiers.iter() {
+        by_skeleton
+            .entry(skeleton(identifier).collect())
+            .or_default()
+            .push(identifier);
+    }
+    println!("\nConfusable identifiers (grouped by skeleton):");
+    for (skeleton, group) in by_skeleton.iter().filter(|(_, group)| group.len() > 1) {
+        println!(
+            "  «{}»: {}",
+            escape_for_display(skeleton),
+            describe_identifiers(group)
+        );
+    }
+
+    println!("\nMixed-script identifiers:");
+    for identifier in identifiers.iter() {
+        if !identifier.as_str().is_single_script() {
+            println!("  {}", describe_identifiers(&[identifier]));
+        }
+    }
+}
+
+fn describe_identifiers(identifiers: &[&str]) -> String {
+    identifiers
+        .iter()
+        .map(|identifier| format!("«{}» ({identifier})", escape_for_display(identifier)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
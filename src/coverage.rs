@@ -0,0 +1,126 @@
+//! Reports which parts of lexlucid's model a set of inputs exercises, and where its
+//! pretokenisation rules overlap.
+
+use std::collections::BTreeMap;
+
+use crate::cleaning;
+use crate::lexlucid::{self, OverlapLength, RejectionCategory};
+use crate::utils::escape_for_display;
+use crate::Edition;
+
+/// Implements the `coverage` CLI command.
+///
+/// Lexes each input with lexlucid, then prints the pretokenisation rules (for the specified
+/// edition) which never produced a pretoken, and the reasons for rejection which were seen.
+///
+/// Rejection reasons are grouped by category, with the first message for each rejection; the
+/// categories which were never seen are listed too.
+pub fn run_coverage_subcommand(inputs: &[&str], edition: Edition) {
+    let mut rejection_counts: BTreeMap<(RejectionCategory, String), usize> = BTreeMap::new();
+    lexlucid::reset_statistics();
+    for input in inputs {
+        let cleaned = cleaning::clean(input);
+        lexlucid::set_statistics_recording(true);
+        let analysis = lexlucid::analyse(&cleaned, edition);
+        lexlucid::set_statistics_recording(false);
+        if let lexlucid::Analysis::Rejects(category, reason) = analysis {
+            let message = reason
+                .into_description()
+                .into_iter()
+                .next()
+                .unwrap_or_else(|| "(no message)".into());
+            *rejection_counts.entry((category, message)).or_insert(0) += 1;
+        }
+    }
+
+    let rule_counts = lexlucid::rule_use_counts(edition);
+    println!("Pretokenisation rule uses:");
+    for (name, count) in rule_counts.iter() {
+        println!("  {count:6}  {name:?}");
+    }
+
+    let mut unused: Vec<_> = rule_counts
+        .iter()
+        .filter(|(_, count)| *count == 0)
+        .map(|(name, _)| format!("{name:?}"))
+        .collect();
+    unused.sort();
+    println!("\nPretokenisation rules never used:");
+    if unused.is_empty() {
+        println!("  (none)");
+    }
+    for name in unused {
+        println!("  {name}");
+    }
+
+    println!("\nRejection reasons seen:");
+    if rejection_counts.is_empty() {
+        println!("  (none)");
+    }
+    for ((category, message), count) in rejection_counts.iter() {
+        println!("  {count:6}  {category:?}: {message}");
+    }
+
+    println!("\nRejection categories never seen:");
+    let unseen: Vec<_> = RejectionCategory::ALL
+        .iter()
+        .filter(|category| !rejection_counts.keys().any(|(seen, _)| seen == *category))
+        .collect();
+    if unseen.is_empty() {
+        println!("  (none)");
+    }
+    for category in unseen {
+        println!("  {category:?}");
+    }
+}
+
+/// Implements the `audit-priority` CLI command.
+///
+/// Lexes each input with lexlucid while recording every position where more than one
+/// pretokenisation rule matched, then prints each pair of (chosen, lower-priority) rules which
+/// overlapped, classified by how the lengths of their matches compared.
+///
+/// The overlaps where the lower-priority rule's match was as long or longer are the places where
+/// the grammar relies on the order of the rules rather than on longest match.
+pub fn run_priority_audit_subcommand(inputs: &[&str], edition: Edition) {
+    lexlucid::reset_statistics();
+    for input in inputs {
+        let cleaned = cleaning::clean(input);
+        lexlucid::set_statistics_recording(true);
+        lexlucid::analyse(&cleaned, edition);
+        lexlucid::set_statistics_recording(false);
+    }
+
+    let overlaps = lexlucid::rule_overlaps();
+    for (length, heading) in [
+        (
+            OverlapLength::Longer,
+            "Priority overrides longest match (the other rule matched more)",
+        ),
+        (
+            OverlapLength::Equal,
+            "Priority breaks a tie (both rules matched the same length)",
+        ),
+        (
+            OverlapLength::Shorter,
+            "Longest match agrees with priority (the other rule matched less)",
+        ),
+    ] {
+        println!("{heading}:");
+        let mut seen = false;
+        for overlap in overlaps.iter().filter(|overlap| overlap.length == length) {
+            seen = true;
+            println!(
+                "  {:6}  {:?} over {:?}, eg «{}»",
+                overlap.count,
+                overlap.chosen,
+                overlap.other,
+                escape_for_display(&overlap.example)
+            );
+        }
+        if !seen {
+            println!("  (none)");
+        }
+        println!();
+    }
+}
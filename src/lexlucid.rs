@@ -1,16 +1,100 @@
 //! Reimplementation of rustc's lexical analysis.
 
+use std::time::{Duration, Instant};
+
 use crate::utils::escape_for_display;
 use crate::Edition;
 
 mod pretokenisation;
 mod reprocessing;
+mod trivia;
 
 pub use pretokenisation::Pretoken;
-pub use reprocessing::{CommentStyle, FineToken, FineTokenData, NumericBase};
+#[cfg(feature = "rustc-harness")]
+pub use pretokenisation::{
+    priority_violation_counts, reset_statistics, rule_overlaps, rule_use_counts, set_rule_tracing,
+    set_statistics_recording, OverlapLength,
+};
+pub use reprocessing::{
+    CommentStyle, FineToken, FineTokenData, FloatValue, NumericBase, StringContinuation,
+};
+pub use trivia::{with_trailing_trivia, WithTrailingTrivia};
 
 const MAX_INPUT_LENGTH: usize = 0x100_0000;
 
+/// The position of a token in the input to lexical analysis, as byte offsets.
+///
+/// `start` is the offset of the token's first byte, and `end` is the offset just past its last
+/// byte (so `&input[span.start..span.end]` is the token's extent).
+///
+/// The offsets are into the input after cleaning (which [`lex`][crate::lex] does first), rather
+/// than into the original source. To relate an offset back to the original source:
+///  - add 3 if the source started with a byte order mark
+///  - add the length of the removed shebang line, including its newline, if there was one
+///  - add 1 for each CRLF sequence in the source before the corresponding position
+#[derive(Copy, Clone, PartialEq, Eq, std::fmt::Debug)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The category of reason for which lexical analysis rejected its input.
+///
+/// This is coarser than the messages which accompany a rejection, so that rejections can be
+/// compared with rustc's.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, std::fmt::Debug)]
+pub enum RejectionCategory {
+    /// No pretokenisation rule matched.
+    NoRuleMatched,
+    /// A reserved form (this includes unterminated comments and literals).
+    ReservedForm,
+    /// A CR in a literal or doc-comment.
+    BareCr,
+    /// A NUL in a C string literal.
+    NulInCString,
+    /// A character which may appear in a character or byte literal only as an escape.
+    EscapeOnlyChar,
+    /// A backslash which doesn't begin any form of escape.
+    UnknownEscape,
+    /// A malformed (or out of range) `\x` escape.
+    InvalidHexEscape,
+    /// A malformed (or out of range) `\u` escape.
+    InvalidUnicodeEscape,
+    /// A non-ASCII character in a byte or byte string literal.
+    NonAscii,
+    /// An identifier, lifetime or label which can't be written in raw form.
+    ForbiddenRawIdentifier,
+    /// A literal suffix consisting of `_` alone.
+    UnderscoreSuffix,
+    /// A numeric literal (or exponent) with no digits.
+    NoDigits,
+    /// A digit which is invalid in the numeric literal's base.
+    InvalidDigit,
+    /// A floating-point literal with a base other than decimal.
+    UnsupportedFloatBase,
+}
+
+impl RejectionCategory {
+    /// All the categories, in order.
+    #[cfg(feature = "rustc-harness")]
+    pub const ALL: [RejectionCategory; 14] = [
+        Self::NoRuleMatched,
+        Self::ReservedForm,
+        Self::BareCr,
+        Self::NulInCString,
+        Self::EscapeOnlyChar,
+        Self::UnknownEscape,
+        Self::InvalidHexEscape,
+        Self::InvalidUnicodeEscape,
+        Self::NonAscii,
+        Self::ForbiddenRawIdentifier,
+        Self::UnderscoreSuffix,
+        Self::NoDigits,
+        Self::InvalidDigit,
+        Self::UnsupportedFloatBase,
+    ];
+}
+
 /// Runs lexical analysis on the specified input.
 ///
 /// If the input is accepted, returns lists of both pretokens and fine-grained tokens.
@@ -21,6 +105,42 @@ const MAX_INPUT_LENGTH: usize = 0x100_0000;
 ///
 /// Panics if the input is longer than 2^24 bytes (this is a sanity check, not part of the model).
 pub fn analyse(input: &str, edition: Edition) -> Analysis {
+    analyse_impl(input, edition, None)
+}
+
+/// Runs lexical analysis on the specified input, measuring how long each step takes.
+///
+/// Behaves as [`analyse`], but also returns the time spent in each step.
+#[cfg(feature = "rustc-harness")]
+pub fn analyse_timed(input: &str, edition: Edition) -> (Analysis, StepTimings) {
+    let mut timings = StepTimings::default();
+    let analysis = analyse_impl(input, edition, Some(&mut timings));
+    (analysis, timings)
+}
+
+/// Time spent in each step of lexical analysis.
+#[derive(Default)]
+pub struct StepTimings {
+    /// Time spent in step 1 (pretokenisation)
+    pub pretokenisation: Duration,
+    /// Time spent in step 2 (reprocessing)
+    pub reprocessing: Duration,
+}
+
+/// Runs `f`, adding the time it took to `elapsed` if that's provided.
+fn timed<T>(elapsed: Option<&mut Duration>, f: impl FnOnce() -> T) -> T {
+    match elapsed {
+        Some(elapsed) => {
+            let start = Instant::now();
+            let result = f();
+            *elapsed += start.elapsed();
+            result
+        }
+        None => f(),
+    }
+}
+
+fn analyse_impl(input: &str, edition: Edition, mut timings: Option<&mut StepTimings>) -> Analysis {
     // Check that the Unicode version claimed by our dependencies matches what we document.
     // The most important one is regex, but that doesn't have a UNICODE_VERSION constant.
     assert_eq!(
@@ -40,55 +160,183 @@ pub fn analyse(input: &str, edition: Edition) -> Analysis {
 
     let mut pretokens = Vec::new();
     let mut tokens = Vec::new();
-    for outcome in pretokenisation::pretokenise(input.into(), edition) {
-        use pretokenisation::Outcome::*;
-        let pretoken = match outcome {
-            Found(pretoken) => pretoken,
-            Rejected(error_message) => {
-                return Analysis::Rejects(Reason::Pretokenisation(
-                    vec![error_message],
-                    pretokens,
-                    tokens,
-                ))
-            }
-            ModelError(messages) => {
-                return Analysis::ModelError(Reason::Pretokenisation(messages, pretokens, tokens))
-            }
-        };
-        match reprocessing::reprocess(&pretoken) {
-            Ok(token) => {
-                pretokens.push(pretoken);
-                tokens.push(token)
-            }
-            Err(reprocessing::Error::Rejected(error_message)) => {
-                return Analysis::Rejects(Reason::Reprocessing(
-                    error_message,
-                    pretoken,
-                    pretokens,
-                    tokens,
-                ));
-            }
-            Err(reprocessing::Error::ModelError(error_message)) => {
-                return Analysis::ModelError(Reason::Reprocessing(
-                    error_message,
-                    pretoken,
-                    pretokens,
-                    tokens,
-                ));
-            }
+    let mut pretokeniser = pretokenisation::pretokenise(input.into(), edition);
+    while let Some(outcome) = timed(
+        timings.as_deref_mut().map(|t| &mut t.pretokenisation),
+        || pretokeniser.next(),
+    ) {
+        match lex_step(
+            outcome,
+            |pretoken| input[pretoken.span.start..].into(),
+            &mut pretokens,
+            &mut tokens,
+            timings.as_deref_mut().map(|t| &mut t.reprocessing),
+        ) {
+            StepOutcome::Lexed => {}
+            StepOutcome::Rejects(category, reason) => return Analysis::Rejects(category, reason),
+            StepOutcome::ModelError(reason) => return Analysis::ModelError(reason),
         }
     }
 
     Analysis::Accepts(pretokens, tokens)
 }
 
+/// Runs lexical analysis on the start of the input, returning the first token.
+///
+/// This lets a caller drive the lexer one token at a time, passing the rest of the input (after
+/// the returned number of characters) in the next call, and stop whenever it likes. Because each
+/// token depends only on the input which follows it, this gives the same tokens as [`analyse`]
+/// (though each token's span is relative to the start of the `input` passed in the call which
+/// returned it).
+///
+/// Like [`analyse`], this expects input which has already been cleaned.
+pub fn lex_one_token(input: &[char], edition: Edition) -> TokenOutcome {
+    let Some(outcome) = pretokenisation::pretokenise_one(input, edition) else {
+        return TokenOutcome::EndOfInput;
+    };
+    let mut pretokens = Vec::new();
+    let mut tokens = Vec::new();
+    match lex_step(
+        outcome,
+        |_| input.iter().collect(),
+        &mut pretokens,
+        &mut tokens,
+        None,
+    ) {
+        StepOutcome::Lexed => {
+            let consumed = pretokens.pop().unwrap().extent.len();
+            TokenOutcome::Lexed(tokens.pop().unwrap(), consumed)
+        }
+        StepOutcome::Rejects(category, reason) => TokenOutcome::Rejects(category, reason),
+        StepOutcome::ModelError(reason) => TokenOutcome::ModelError(reason),
+    }
+}
+
+/// Runs one step of lexical analysis: reprocesses the pretokeniser's outcome for the next
+/// pretoken, and on success appends the pretoken and the resulting token to the lists of what has
+/// been lexed so far.
+///
+/// On rejection or model error, the lists are moved into the returned [`Reason`].
+///
+/// `rest` returns the input from the start of the pretoken. It's called only when reprocessing
+/// rejects a reserved form, to check whether that looks like a mistyped raw string.
+fn lex_step(
+    outcome: pretokenisation::Outcome,
+    rest: impl FnOnce(&Pretoken) -> String,
+    pretokens: &mut Vec<Pretoken>,
+    tokens: &mut Vec<FineToken>,
+    reprocessing_time: Option<&mut Duration>,
+) -> StepOutcome {
+    use pretokenisation::Outcome::*;
+    let lexed = |pretokens: &mut Vec<_>, tokens: &mut Vec<_>| {
+        (std::mem::take(pretokens), std::mem::take(tokens))
+    };
+    let pretoken = match outcome {
+        Found(pretoken) => pretoken,
+        Rejected(error_message) => {
+            // The only pretokenisation rejection in use is "no rule matched" (rules can force an
+            // error, but none currently do).
+            let (pretokens, tokens) = lexed(pretokens, tokens);
+            return StepOutcome::Rejects(
+                RejectionCategory::NoRuleMatched,
+                Reason::Pretokenisation(vec![error_message], pretokens, tokens),
+            );
+        }
+        ModelError(messages) => {
+            let (pretokens, tokens) = lexed(pretokens, tokens);
+            return StepOutcome::ModelError(Reason::Pretokenisation(messages, pretokens, tokens));
+        }
+    };
+    match timed(reprocessing_time, || reprocessing::reprocess(&pretoken)) {
+        Ok(token) => {
+            pretokens.push(pretoken);
+            tokens.push(token);
+            StepOutcome::Lexed
+        }
+        Err(reprocessing::Error::Rejected(category, error_message)) => {
+            let error_message = match category {
+                RejectionCategory::ReservedForm => {
+                    reprocessing::describe_raw_string_near_miss(&rest(&pretoken))
+                        .unwrap_or(error_message)
+                }
+                _ => error_message,
+            };
+            let (pretokens, tokens) = lexed(pretokens, tokens);
+            StepOutcome::Rejects(
+                category,
+                Reason::Reprocessing(error_message, pretoken, pretokens, tokens),
+            )
+        }
+        Err(reprocessing::Error::ModelError(error_message)) => {
+            let (pretokens, tokens) = lexed(pretokens, tokens);
+            StepOutcome::ModelError(Reason::Reprocessing(
+                error_message,
+                pretoken,
+                pretokens,
+                tokens,
+            ))
+        }
+    }
+}
+
+/// Result of [`lex_step`].
+enum StepOutcome {
+    /// The step appended a pretoken and token to the lists.
+    Lexed,
+    Rejects(RejectionCategory, Reason),
+    ModelError(Reason),
+}
+
+/// Result of [`lex_one_token`].
+pub enum TokenOutcome {
+    /// Lexical analysis found a token, made from the given number of characters.
+    Lexed(FineToken, usize),
+
+    /// The input was empty.
+    EndOfInput,
+
+    /// Lexical analysis rejected the start of the input.
+    ///
+    /// Only the tests look at the category.
+    #[cfg_attr(not(test), allow(dead_code))]
+    Rejects(RejectionCategory, Reason),
+
+    /// The input demonstrated a problem in lexlucid's model or implementation.
+    ModelError(Reason),
+}
+
+/// Runs lexical analysis on the input only for long enough to find a token which isn't whitespace
+/// or a non-doc-comment, and returns that token.
+///
+/// Returns None if there is no such token, or if lexical analysis rejects the input (or reports a
+/// model error) before finding one.
+///
+/// This is a lookahead rather than part of an analysis, so it isn't traced (see
+/// [`set_rule_tracing`]).
+pub(crate) fn first_significant_token(input: &str, edition: Edition) -> Option<FineToken> {
+    pretokenisation::without_rule_tracing(|| {
+        for outcome in pretokenisation::pretokenise(input.into(), edition) {
+            let pretokenisation::Outcome::Found(pretoken) = outcome else {
+                return None;
+            };
+            let token = reprocessing::reprocess(&pretoken).ok()?;
+            if !token.data.is_whitespace() {
+                return Some(token);
+            }
+        }
+        None
+    })
+}
+
 /// Result of running lexical analysis on a string.
 pub enum Analysis {
     /// Lexical analysis accepted the input.
+    #[cfg_attr(not(feature = "rustc-harness"), allow(dead_code))]
     Accepts(Vec<Pretoken>, Vec<FineToken>),
 
     /// Lexical analysis rejected the input.
-    Rejects(Reason),
+    #[cfg_attr(not(feature = "rustc-harness"), allow(dead_code))]
+    Rejects(RejectionCategory, Reason),
 
     /// The input demonstrated a problem in lexlucid's model or implementation.
     ModelError(Reason),
@@ -112,6 +360,7 @@ pub enum Reason {
     /// encountered a problem with the model).
     ///
     /// The token lists represent what was lexed successfully first.
+    #[cfg_attr(not(feature = "rustc-harness"), allow(dead_code))]
     Reprocessing(String, Pretoken, Vec<Pretoken>, Vec<FineToken>),
 }
 
@@ -140,3 +389,6 @@ impl Reason {
         description
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -1,9 +1,19 @@
 //! Step 1 (pretokenisation) of lexical analysis.
 
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
 use regex::{Captures, Regex};
 
 use crate::{
-    char_sequences::Charseq, lexlucid::pretokenisation::regex_utils::constrained_captures, Edition,
+    char_sequences::Charseq,
+    lexlucid::{
+        pretokenisation::regex_utils::{constrained_captures, constrained_captures_among},
+        Span,
+    },
+    Edition,
 };
 use regex_utils::pretokeniser_regex;
 
@@ -11,6 +21,9 @@ mod function_rules;
 mod pretokenisation_rules;
 mod regex_utils;
 
+pub use pretokenisation_rules::RuleName;
+use pretokenisation_rules::RULE_NAME_COUNT;
+
 macro_rules! make_regex {
     ($re:literal $(,)?) => {{
         static RE: ::std::sync::OnceLock<regex::Regex> = ::std::sync::OnceLock::new();
@@ -26,6 +39,13 @@ pub struct Pretoken {
 
     /// The input characters which make up the token.
     pub extent: Charseq,
+
+    /// The token's position in the input, as byte offsets.
+    pub span: Span,
+
+    /// The pretokenisation rule which produced the pretoken.
+    #[cfg(any(test, feature = "rustc-harness"))]
+    pub rule: RuleName,
 }
 
 impl Pretoken {
@@ -113,6 +133,7 @@ pub fn pretokenise(input: Charseq, edition: Edition) -> impl Iterator<Item = Out
         rules: pretokenisation_rules::list_rules(edition),
         input,
         index: 0,
+        byte_index: 0,
     }
 }
 
@@ -133,9 +154,12 @@ pub enum Outcome {
 }
 
 struct Pretokeniser {
-    rules: &'static Vec<&'static Rule>,
+    rules: &'static Vec<(RuleName, &'static Rule)>,
     input: Charseq,
+    /// Offset of the next character to examine, in characters.
     index: usize,
+    /// Offset of the next character to examine, in bytes.
+    byte_index: usize,
 }
 
 impl Iterator for Pretokeniser {
@@ -146,18 +170,46 @@ impl Iterator for Pretokeniser {
         if rest.is_empty() {
             return None;
         }
-        use Outcome::*;
-        match lex_one_pretoken(self.rules, rest) {
-            LexOutcome::Lexed(pretoken) => {
-                self.index += pretoken.extent.len();
-                Some(Outcome::Found(pretoken))
-            }
-            LexOutcome::NoRuleMatched => Some(Rejected("no rule matched".into())),
-            LexOutcome::ForcedError(message) => Some(Rejected(message)),
-            LexOutcome::PriorityViolation { best, violators } => {
-                Some(ModelError(describe_priority_violations(best, violators)))
-            }
+        let outcome = pretokenise_one_at(self.rules, rest, self.byte_index);
+        if let Outcome::Found(pretoken) = &outcome {
+            self.index += pretoken.extent.len();
+            self.byte_index = pretoken.span.end;
         }
+        Some(outcome)
+    }
+}
+
+/// Extracts a single pretoken from the start of the input.
+///
+/// Returns None if the input is empty. Otherwise the outcome is as for [`pretokenise`] (the
+/// pretoken's span is relative to the start of `input`).
+pub fn pretokenise_one(input: &[char], edition: Edition) -> Option<Outcome> {
+    if input.is_empty() {
+        return None;
+    }
+    Some(pretokenise_one_at(
+        pretokenisation_rules::list_rules(edition),
+        input,
+        0,
+    ))
+}
+
+/// Extracts a single pretoken from `rest`, which is at `byte_offset` in the complete input.
+fn pretokenise_one_at(
+    rules: &Vec<(RuleName, &Rule)>,
+    rest: &[char],
+    byte_offset: usize,
+) -> Outcome {
+    use Outcome::*;
+    match lex_one_pretoken(rules, rest, byte_offset) {
+        LexOutcome::Lexed(pretoken) => Found(pretoken),
+        LexOutcome::NoRuleMatched => Rejected("no rule matched".into()),
+        LexOutcome::ForcedError(message) => Rejected(message),
+        LexOutcome::PriorityViolation {
+            best,
+            violators,
+            category,
+        } => ModelError(describe_priority_violations(best, violators, category)),
     }
 }
 
@@ -169,28 +221,219 @@ impl Iterator for Pretokeniser {
 ///
 /// Reports PriorityViolation if any lower-priority rule succeeded as many, or more, characters.
 /// (This is checking that priority-based and longest-match-based formulations would be equivalent.)
-fn lex_one_pretoken(rules: &Vec<&Rule>, rest: &[char]) -> LexOutcome {
+///
+/// `byte_offset` is the position of `rest` in the complete input, in bytes.
+///
+/// If rule tracing is enabled (see [`set_rule_tracing`]), prints each successful rule's match and
+/// the chosen rule to standard error.
+fn lex_one_pretoken(
+    rules: &Vec<(RuleName, &Rule)>,
+    rest: &[char],
+    byte_offset: usize,
+) -> LexOutcome {
     use LexOutcome::*;
+    let tracing = RULE_TRACING.load(Ordering::Relaxed) && !RULE_TRACING_SUSPENDED.get();
+    let recording_statistics = RECORDING_STATISTICS.load(Ordering::Relaxed);
     let mut matches = Vec::new();
-    for rule in rules {
+    let mut matched_rules = Vec::new();
+    let mut chosen_rule = None;
+    for (name, rule) in rules {
         match rule.apply(rest) {
             RuleOutcome::Success(token_length, data) => {
+                if tracing {
+                    eprintln!("trace: at byte {byte_offset}: {name:?} matched {token_length} chars: {data:?}");
+                }
+                if recording_statistics {
+                    matched_rules.push((*name, token_length));
+                }
+                chosen_rule.get_or_insert(*name);
+                let extent = &rest[..token_length];
+                let byte_length: usize = extent.iter().map(|c| c.len_utf8()).sum();
                 matches.push(Pretoken {
                     data,
-                    extent: rest[..token_length].into(),
+                    extent: extent.into(),
+                    span: Span {
+                        start: byte_offset,
+                        end: byte_offset + byte_length,
+                    },
+                    #[cfg(any(test, feature = "rustc-harness"))]
+                    rule: *name,
                 });
             }
             RuleOutcome::Failure => {}
             RuleOutcome::ForceError(message) => return ForcedError(message),
         }
     }
-    if matches.is_empty() {
-        NoRuleMatched
-    } else {
-        resolve(matches)
+    let Some(chosen_rule) = chosen_rule else {
+        if tracing {
+            eprintln!("trace: at byte {byte_offset}: no rule matched");
+        }
+        return NoRuleMatched;
+    };
+    if tracing {
+        eprintln!("trace: at byte {byte_offset}: chose {chosen_rule:?}");
+    }
+    if matched_rules.len() > 1 {
+        record_rule_overlaps(rest, &matched_rules);
+    }
+    let outcome = resolve(matches);
+    if recording_statistics && matches!(outcome, Lexed(_)) {
+        RULE_USE_COUNTS[chosen_rule as usize].fetch_add(1, Ordering::Relaxed);
+    }
+    outcome
+}
+
+static RULE_TRACING: AtomicBool = AtomicBool::new(false);
+
+/// Turns tracing of pretokenisation rule attempts on or off (for the whole process).
+///
+/// While tracing is on, each time the pretokeniser extracts a pretoken it prints a line to
+/// standard error for each rule which matched (giving the rule, the number of characters it
+/// matched, and the resulting pretoken data), and a line saying which rule was chosen.
+///
+/// Tracing is suspended while running lexing which isn't part of the analysis being traced (see
+/// [`without_rule_tracing`]).
+#[cfg(feature = "rustc-harness")]
+pub fn set_rule_tracing(enabled: bool) {
+    RULE_TRACING.store(enabled, Ordering::Relaxed);
+}
+
+thread_local! {
+    /// Whether rule tracing is suspended on this thread (see [`without_rule_tracing`]).
+    static RULE_TRACING_SUSPENDED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs `f` with rule tracing suspended on this thread.
+///
+/// This is for lexing which isn't part of the analysis being traced, such as cleaning's lookahead
+/// to decide whether the input starts with a shebang line.
+pub fn without_rule_tracing<T>(f: impl FnOnce() -> T) -> T {
+    let was_suspended = RULE_TRACING_SUSPENDED.replace(true);
+    let result = f();
+    RULE_TRACING_SUSPENDED.set(was_suspended);
+    result
+}
+
+static RULE_USE_COUNTS: [AtomicUsize; RULE_NAME_COUNT] =
+    [const { AtomicUsize::new(0) }; RULE_NAME_COUNT];
+
+/// Returns how many pretokens each of the specified edition's rules has produced while
+/// [statistics recording][set_statistics_recording] was on (since the last
+/// [`reset_statistics`]), in priority order.
+///
+/// The counts include pretokens produced while lexing in other editions.
+#[cfg(feature = "rustc-harness")]
+pub fn rule_use_counts(edition: Edition) -> Vec<(RuleName, usize)> {
+    pretokenisation_rules::list_rules(edition)
+        .iter()
+        .map(|(name, _)| {
+            (
+                *name,
+                RULE_USE_COUNTS[*name as usize].load(Ordering::Relaxed),
+            )
+        })
+        .collect()
+}
+
+static RECORDING_STATISTICS: AtomicBool = AtomicBool::new(false);
+
+/// Turns recording of pretokenisation statistics on or off (for the whole process).
+///
+/// While recording is on, the pretokeniser counts the pretokens each rule produces (see
+/// [`rule_use_counts`]) and the priority violations it sees (see [`priority_violation_counts`]),
+/// and each time more than one rule matches at the same position it records the chosen rule and
+/// each other rule which matched (see [`rule_overlaps`]).
+///
+/// Nothing is recorded while it's off, so callers which want statistics for only their own lexing
+/// should turn it on just around that lexing, after calling [`reset_statistics`].
+#[cfg(feature = "rustc-harness")]
+pub fn set_statistics_recording(enabled: bool) {
+    RECORDING_STATISTICS.store(enabled, Ordering::Relaxed);
+}
+
+/// Discards the pretokenisation statistics recorded so far.
+#[cfg(feature = "rustc-harness")]
+pub fn reset_statistics() {
+    for count in &RULE_USE_COUNTS {
+        count.store(0, Ordering::Relaxed);
+    }
+    for count in &PRIORITY_VIOLATION_COUNTS {
+        count.store(0, Ordering::Relaxed);
+    }
+    RULE_OVERLAPS.lock().unwrap().clear();
+}
+
+/// Chosen rule, other rule, and how the other rule's match length compared.
+type OverlapKey = (RuleName, RuleName, OverlapLength);
+
+/// For each overlap seen, how many times it was seen and an example.
+static RULE_OVERLAPS: Mutex<BTreeMap<OverlapKey, (usize, String)>> = Mutex::new(BTreeMap::new());
+
+/// How the length of a lower-priority rule's match compares with the length of the chosen rule's.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, std::fmt::Debug)]
+pub enum OverlapLength {
+    /// The lower-priority rule matched fewer characters (so choosing the longest match would give
+    /// the same result).
+    Shorter,
+    /// Both rules matched the same number of characters (so priority breaks the tie).
+    Equal,
+    /// The lower-priority rule matched more characters (so priority overrides longest match).
+    Longer,
+}
+
+/// A pair of rules which both matched at the same position, as recorded while
+/// [statistics recording][set_statistics_recording] was on.
+#[cfg(feature = "rustc-harness")]
+pub struct RuleOverlap {
+    /// The highest-priority rule which matched.
+    pub chosen: RuleName,
+    /// A lower-priority rule which also matched.
+    pub other: RuleName,
+    pub length: OverlapLength,
+    /// How many times this overlap was seen.
+    pub count: usize,
+    /// The characters matched by the longer of the two rules, from the shortest example seen.
+    pub example: String,
+}
+
+fn record_rule_overlaps(rest: &[char], matched_rules: &[(RuleName, usize)]) {
+    let (chosen, chosen_length) = matched_rules[0];
+    let mut overlaps = RULE_OVERLAPS.lock().unwrap();
+    for &(other, other_length) in &matched_rules[1..] {
+        let length = match other_length.cmp(&chosen_length) {
+            std::cmp::Ordering::Less => OverlapLength::Shorter,
+            std::cmp::Ordering::Equal => OverlapLength::Equal,
+            std::cmp::Ordering::Greater => OverlapLength::Longer,
+        };
+        let example = &rest[..chosen_length.max(other_length)];
+        let (count, shortest_example) = overlaps
+            .entry((chosen, other, length))
+            .or_insert_with(|| (0, example.iter().collect()));
+        *count += 1;
+        if example.len() < shortest_example.chars().count() {
+            *shortest_example = example.iter().collect();
+        }
     }
 }
 
+/// Returns the rule overlaps recorded while [statistics recording][set_statistics_recording] was
+/// on (since the last [`reset_statistics`]), ordered by chosen rule, then other rule, then length.
+#[cfg(feature = "rustc-harness")]
+pub fn rule_overlaps() -> Vec<RuleOverlap> {
+    RULE_OVERLAPS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(&(chosen, other, length), (count, example))| RuleOverlap {
+            chosen,
+            other,
+            length,
+            count: *count,
+            example: example.clone(),
+        })
+        .collect()
+}
+
 enum LexOutcome {
     /// At least one rule matched, and there was no priority violation.
     Lexed(Pretoken),
@@ -205,6 +448,8 @@ enum LexOutcome {
         best: Pretoken,
         /// The pretoken from lower-priority rules which were unexpectedly long.
         violators: Vec<Pretoken>,
+        /// The classification of this violation.
+        category: PriorityViolationCategory,
     },
 
     /// A rule requested a forced lexer error (not currently used).
@@ -220,58 +465,179 @@ fn resolve(matches: Vec<Pretoken>) -> LexOutcome {
     let violators: Vec<_> = iter
         .filter(|pretoken| pretoken.char_length() >= best_length)
         .collect();
-    if violators.is_empty() || is_exception_to_longest_match_principle(&best, &violators) {
+    if violators.is_empty() {
+        return Lexed(best);
+    }
+    let category = categorise_priority_violation(&best, &violators);
+    if RECORDING_STATISTICS.load(Ordering::Relaxed) {
+        PRIORITY_VIOLATION_COUNTS[category as usize].fetch_add(1, Ordering::Relaxed);
+    }
+    if category.is_known_exception() {
         Lexed(best)
     } else {
-        PriorityViolation { best, violators }
+        PriorityViolation {
+            best,
+            violators,
+            category,
+        }
     }
 }
 
-/// Returns true if we have a known exception to the "unique longest match" principle.
+/// Classification of cases where a lower-priority rule matched at least as many characters as the
+/// highest-priority successful rule.
 ///
 /// We want to be able to write that the priority-based system for choosing a successful rule gives
 /// the same result as choosing the rule which matched the longest sequence of characters, with only
 /// known exceptions.
 ///
-/// At present the only exception is that an additional rule for a decimal integer literal may
-/// succeed when the chosen rule is for a non-decimal float or integer literal (eg for `0x3` or
-/// `0b1e2`).
+/// At present the only known exception is
+/// [`NondecimalNumericVersusDecimalInteger`][`Self::NondecimalNumericVersusDecimalInteger`]. The
+/// other categories are violations of the principle, classified to suggest where to look.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, std::fmt::Debug)]
+pub enum PriorityViolationCategory {
+    /// An additional rule for a decimal integer literal succeeded when the chosen rule is for a
+    /// non-decimal float or integer literal (eg for `0x3` or `0b1e2`).
+    NondecimalNumericVersusDecimalInteger,
+    /// All the pretokens involved are numeric literals.
+    NumericLiterals,
+    /// One of the pretokens involved is a reserved form.
+    ReservedForms,
+    /// All the pretokens involved are identifiers, lifetimes, or labels.
+    IdentifierLike,
+    /// All the pretokens involved are quoted literals.
+    QuotedLiterals,
+    /// Not recognised as any of the above.
+    Unrecognised,
+}
+
+impl PriorityViolationCategory {
+    /// All categories, in order.
+    pub const ALL: [PriorityViolationCategory; 6] = [
+        Self::NondecimalNumericVersusDecimalInteger,
+        Self::NumericLiterals,
+        Self::ReservedForms,
+        Self::IdentifierLike,
+        Self::QuotedLiterals,
+        Self::Unrecognised,
+    ];
+
+    /// Says whether this category is a known (accepted) exception to the longest-match principle.
+    pub fn is_known_exception(self) -> bool {
+        self == Self::NondecimalNumericVersusDecimalInteger
+    }
+
+    /// Suggests where to look for the cause of a violation in this category.
+    fn suggestion(self) -> &'static str {
+        match self {
+            Self::NondecimalNumericVersusDecimalInteger => "this is a known exception",
+            Self::NumericLiterals => {
+                "check the ordering of the numeric literal rules, and their handling of exponents \
+                 and suffixes"
+            }
+            Self::ReservedForms => {
+                "check whether the reserved-form rules should be listed before (or after) the \
+                 other rules which matched"
+            }
+            Self::IdentifierLike => "check the identifier, lifetime, and label rules' prefixes",
+            Self::QuotedLiterals => {
+                "check the quoted literal rules' handling of prefixes, suffixes, and terminators"
+            }
+            Self::Unrecognised => "this doesn't look like any previously-seen violation",
+        }
+    }
+}
+
+static PRIORITY_VIOLATION_COUNTS: [AtomicUsize; PriorityViolationCategory::ALL.len()] =
+    [const { AtomicUsize::new(0) }; PriorityViolationCategory::ALL.len()];
+
+/// Returns how many times each category of priority violation has been seen while
+/// [statistics recording][set_statistics_recording] was on (since the last
+/// [`reset_statistics`]), including known exceptions.
+///
+/// Categories which haven't been seen are omitted.
+#[cfg(feature = "rustc-harness")]
+pub fn priority_violation_counts() -> Vec<(PriorityViolationCategory, usize)> {
+    PriorityViolationCategory::ALL
+        .iter()
+        .map(|category| {
+            (
+                *category,
+                PRIORITY_VIOLATION_COUNTS[*category as usize].load(Ordering::Relaxed),
+            )
+        })
+        .filter(|(_, count)| *count != 0)
+        .collect()
+}
+
+/// Classifies a priority violation.
 ///
 /// 'best' is the pretoken from the highest-priority successful rule.
 /// 'violators' are the pretokens from successful rules which are at least as long as 'best'.
-fn is_exception_to_longest_match_principle(best: &Pretoken, violators: &Vec<Pretoken>) -> bool {
+fn categorise_priority_violation(
+    best: &Pretoken,
+    violators: &[Pretoken],
+) -> PriorityViolationCategory {
+    use PretokenData::*;
+    use PriorityViolationCategory::*;
     fn is_decimal_integer_literal(pretoken: &Pretoken) -> bool {
+        matches!(pretoken.data, IntegerDecimalLiteral { .. })
+    }
+    fn is_nondecimal_numeric_literal(pretoken: &Pretoken) -> bool {
         matches!(
-            pretoken,
-            Pretoken {
-                data: PretokenData::IntegerDecimalLiteral { .. },
-                ..
-            }
+            pretoken.data,
+            IntegerBinaryLiteral { .. }
+                | IntegerOctalLiteral { .. }
+                | IntegerHexadecimalLiteral { .. }
+                | FloatLiteral { has_base: true, .. }
         )
     }
-    fn is_nondecimal_numeric_literal(pretoken: &Pretoken) -> bool {
+    fn is_numeric_literal(pretoken: &Pretoken) -> bool {
+        is_decimal_integer_literal(pretoken)
+            || is_nondecimal_numeric_literal(pretoken)
+            || matches!(pretoken.data, FloatLiteral { .. })
+    }
+    fn is_identifier_like(pretoken: &Pretoken) -> bool {
         matches!(
-            pretoken,
-            Pretoken {
-                data: PretokenData::IntegerBinaryLiteral { .. }
-                    | PretokenData::IntegerOctalLiteral { .. }
-                    | PretokenData::IntegerHexadecimalLiteral { .. }
-                    | PretokenData::FloatLiteral { has_base: true, .. },
-                ..
-            }
+            pretoken.data,
+            Identifier { .. }
+                | RawIdentifier { .. }
+                | LifetimeOrLabel { .. }
+                | RawLifetimeOrLabel { .. }
         )
     }
-    if is_nondecimal_numeric_literal(&best)
+    fn is_quoted_literal(pretoken: &Pretoken) -> bool {
+        matches!(
+            pretoken.data,
+            SingleQuoteLiteral { .. } | DoubleQuoteLiteral { .. } | RawDoubleQuoteLiteral { .. }
+        )
+    }
+
+    if is_nondecimal_numeric_literal(best)
         && violators.len() == 1
         && is_decimal_integer_literal(&violators[0])
         && violators[0].char_length() == best.char_length()
     {
-        return true;
+        return NondecimalNumericVersusDecimalInteger;
+    }
+    let all = || std::iter::once(best).chain(violators);
+    if all().all(is_numeric_literal) {
+        NumericLiterals
+    } else if all().any(|pretoken| matches!(pretoken.data, Reserved)) {
+        ReservedForms
+    } else if all().all(is_identifier_like) {
+        IdentifierLike
+    } else if all().all(is_quoted_literal) {
+        QuotedLiterals
+    } else {
+        Unrecognised
     }
-    false
 }
 
-fn describe_priority_violations(best: Pretoken, violators: Vec<Pretoken>) -> Vec<String> {
+fn describe_priority_violations(
+    best: Pretoken,
+    violators: Vec<Pretoken>,
+    category: PriorityViolationCategory,
+) -> Vec<String> {
     let mut messages = vec![
         "matched multiple ways with surprising lengths".into(),
         "highest-priority match:".into(),
@@ -281,6 +647,8 @@ fn describe_priority_violations(best: Pretoken, violators: Vec<Pretoken>) -> Vec
     for pretoken in violators {
         messages.push(format!("  {:?} {:?}", pretoken.extent, pretoken.data));
     }
+    messages.push(format!("category: {:?}", category));
+    messages.push(format!("suggestion: {}", category.suggestion()));
     messages
 }
 
@@ -296,6 +664,7 @@ enum Rule {
         re: Regex,
         precheck_re: Regex,
         constraint: fn(&Captures) -> bool,
+        candidate_lengths: Option<fn(&str) -> Vec<usize>>,
         extract_data: fn(&Captures) -> PretokenData,
     },
 }
@@ -331,6 +700,25 @@ impl Rule {
             re: pretokeniser_regex(re),
             precheck_re: pretokeniser_regex(precheck_re),
             constraint,
+            candidate_lengths: None,
+            extract_data,
+        }
+    }
+
+    /// Makes a constrained regex rule which tries only the prefixes whose lengths are returned by
+    /// `candidate_lengths` (see [`constrained_captures_among`] for what it must return).
+    fn new_constrained_regex_with_candidates(
+        extract_data: fn(&Captures) -> PretokenData,
+        constraint: fn(&Captures) -> bool,
+        candidate_lengths: fn(&str) -> Vec<usize>,
+        precheck_re: &str,
+        re: &str,
+    ) -> Self {
+        Self::ConstrainedRegex {
+            re: pretokeniser_regex(re),
+            precheck_re: pretokeniser_regex(precheck_re),
+            constraint,
+            candidate_lengths: Some(candidate_lengths),
             extract_data,
         }
     }
@@ -347,8 +735,16 @@ impl Rule {
                 re,
                 precheck_re,
                 constraint,
+                candidate_lengths,
                 extract_data,
-            } => apply_constrained_regex_rule(re, precheck_re, *constraint, input, *extract_data),
+            } => apply_constrained_regex_rule(
+                re,
+                precheck_re,
+                *constraint,
+                *candidate_lengths,
+                input,
+                *extract_data,
+            ),
         }
     }
 }
@@ -407,10 +803,14 @@ fn apply_regex_rule(
 ///
 /// The constraint function is given the captures from a successful match of `re`. It must return
 /// true iff the constraint is satisfied.
+///
+/// If `candidate_lengths` is provided, only the prefixes it returns are tried (this too is intended
+/// only to avoid slowness).
 fn apply_constrained_regex_rule(
     re: &Regex,
     precheck_re: &Regex,
     constraint: fn(&Captures) -> bool,
+    candidate_lengths: Option<fn(&str) -> Vec<usize>>,
     input: &[char],
     extract_data: fn(&Captures) -> PretokenData,
 ) -> RuleOutcome {
@@ -418,7 +818,13 @@ fn apply_constrained_regex_rule(
     if !precheck_re.is_match(&s) {
         return RuleOutcome::Failure;
     }
-    let Some(captures) = constrained_captures(re, constraint, &s) else {
+    let found = match candidate_lengths {
+        Some(candidate_lengths) => {
+            constrained_captures_among(re, constraint, &s, candidate_lengths(&s))
+        }
+        None => constrained_captures(re, constraint, &s),
+    };
+    let Some(captures) = found else {
         return RuleOutcome::Failure;
     };
     let mtch = captures.get(0).unwrap();
@@ -12,10 +12,14 @@ use crate::{char_sequences::Charseq, Edition};
 
 use super::{PretokenData, Rule};
 
-pub fn list_rules(edition: Edition) -> &'static Vec<&'static Rule> {
-    static EDITION_2015_RULES: OnceLock<Vec<&'static Rule>> = OnceLock::new();
-    static EDITION_2021_RULES: OnceLock<Vec<&'static Rule>> = OnceLock::new();
-    static EDITION_2024_RULES: OnceLock<Vec<&'static Rule>> = OnceLock::new();
+/// Returns the pretokenisation rules for the specified edition, in priority order.
+///
+/// Each rule (and so each of its regular expressions) is built only once per process, and rules
+/// are shared between the editions which use them.
+pub fn list_rules(edition: Edition) -> &'static Vec<(RuleName, &'static Rule)> {
+    static EDITION_2015_RULES: OnceLock<Vec<(RuleName, &'static Rule)>> = OnceLock::new();
+    static EDITION_2021_RULES: OnceLock<Vec<(RuleName, &'static Rule)>> = OnceLock::new();
+    static EDITION_2024_RULES: OnceLock<Vec<(RuleName, &'static Rule)>> = OnceLock::new();
     match edition {
         Edition::E2015 => EDITION_2015_RULES.get_or_init(|| make_rules(RULES_FOR_EDITION_2015)),
         Edition::E2021 => EDITION_2021_RULES.get_or_init(|| make_rules(RULES_FOR_EDITION_2021)),
@@ -23,8 +27,12 @@ pub fn list_rules(edition: Edition) -> &'static Vec<&'static Rule> {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
-enum RuleName {
+/// Identifies a pretokenisation rule.
+///
+/// Rules whose names end with an edition are used only from that edition onwards (or, for `2015`,
+/// only before Rust 2021).
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, std::fmt::Debug)]
+pub enum RuleName {
     Whitespace,
     LineComment,
     BlockComment,
@@ -54,6 +62,11 @@ enum RuleName {
     NonrawIdentifier,
 }
 
+/// The number of `RuleName`s.
+///
+/// This relies on `NonrawIdentifier` being the last variant.
+pub const RULE_NAME_COUNT: usize = RuleName::NonrawIdentifier as usize + 1;
+
 const RULES_FOR_EDITION_2015: &[RuleName] = [
     RuleName::Whitespace,
     RuleName::LineComment,
@@ -131,10 +144,13 @@ const RULES_FOR_EDITION_2024: &[RuleName] = [
 ]
 .as_slice();
 
-fn make_rules(wanted: &[RuleName]) -> Vec<&'static Rule> {
+fn make_rules(wanted: &[RuleName]) -> Vec<(RuleName, &'static Rule)> {
     static NAMED_RULES: OnceLock<BTreeMap<RuleName, Rule>> = OnceLock::new();
     let named_rules = NAMED_RULES.get_or_init(make_named_rules);
-    wanted.iter().map(|name| &named_rules[name]).collect()
+    wanted
+        .iter()
+        .map(|name| (*name, &named_rules[name]))
+        .collect()
 }
 
 #[rustfmt::skip]
@@ -160,9 +176,9 @@ fn make_named_rules() -> BTreeMap<RuleName, Rule> {
 
        // Block comment
        (RuleName::BlockComment,
-        Rule::new_constrained_regex (
+        Rule::new_constrained_regex_with_candidates (
             |cp| PretokenData::BlockComment{ comment_content: cp["comment_content"].into() },
-            block_comment_constraint, r##"\A
+            block_comment_constraint, block_comment_candidate_lengths, r##"\A
                 / \*
             "##, r##"\A
                 / \*
@@ -698,3 +714,39 @@ pub fn block_comment_constraint(captures: &Captures) -> bool {
     }
     depth == 0
 }
+
+/// Candidate lengths for the block comment rule.
+///
+/// Scans the haystack (which must begin with `/*`) once, using the same nesting rules as
+/// [`block_comment_constraint`], and returns the length of the shortest prefix which ends with a
+/// `*/` bringing the nesting depth back to zero (if there is one).
+///
+/// Longer prefixes needn't be tried: the prefix following that one doesn't end with `*/`, so it
+/// doesn't match the rule's pattern.
+pub fn block_comment_candidate_lengths(haystack: &str) -> Vec<usize> {
+    let mut depth = 0_isize;
+    let mut after_slash = false;
+    let mut after_star = false;
+    for (idx, c) in haystack.char_indices() {
+        match c {
+            '*' if after_slash => {
+                depth += 1;
+                after_slash = false;
+            }
+            '/' if after_star => {
+                depth -= 1;
+                // Depth doesn't drop below zero because we stop when it reaches zero.
+                assert!(depth >= 0);
+                if depth == 0 {
+                    return vec![idx + 1];
+                }
+                after_star = false;
+            }
+            _ => {
+                after_slash = c == '/';
+                after_star = c == '*';
+            }
+        }
+    }
+    Vec::new()
+}
@@ -24,15 +24,43 @@ pub fn constrained_captures<'hs>(
     constraint: fn(&Captures) -> bool,
     haystack: &'hs str,
 ) -> Option<Captures<'hs>> {
-    let prefixes = haystack
+    let lengths = haystack
         .char_indices()
-        .map(|(idx, _)| &haystack[..idx])
-        .chain(once(haystack));
+        .map(|(idx, _)| idx)
+        .chain(once(haystack.len()));
+    constrained_captures_among(re, constraint, haystack, lengths)
+}
+
+/// Matches a regular expression against a string, requiring a constraint to be satisfied, trying
+/// only some of the haystack's prefixes.
+///
+/// This gives the same result as [`constrained_captures`], but tries only the prefixes whose
+/// lengths (in bytes) are in `candidate_lengths`, which must be in increasing order.
+///
+/// `candidate_lengths` must include the length of every prefix which matches `re` and satisfies
+/// the constraint, except that it may stop after the first such prefix which is not immediately
+/// followed (ie, with one more character) by another.
+pub fn constrained_captures_among<'hs>(
+    re: &Regex,
+    constraint: fn(&Captures) -> bool,
+    haystack: &'hs str,
+    candidate_lengths: impl IntoIterator<Item = usize>,
+) -> Option<Captures<'hs>> {
     let mut longest_found = None;
-    for candidate in prefixes {
-        match re.captures(candidate) {
+    // The length of the prefix with one more character than the longest found so far
+    let mut next_length = None;
+    for length in candidate_lengths {
+        if longest_found.is_some() && Some(length) != next_length {
+            // The prefix which was skipped doesn't satisfy the constraint
+            break;
+        }
+        match re.captures(&haystack[..length]) {
             Some(captures) if constraint(&captures) => {
                 longest_found = Some(captures);
+                next_length = haystack[length..]
+                    .chars()
+                    .next()
+                    .map(|c| length + c.len_utf8());
             }
             _ if longest_found.is_some() => break,
             _ => {}
@@ -2,7 +2,10 @@ use regex::Captures;
 
 use crate::lexlucid::pretokenisation::regex_utils::pretokeniser_regex;
 
-use super::constrained_captures;
+use super::super::pretokenisation_rules::{
+    block_comment_candidate_lengths, block_comment_constraint,
+};
+use super::{constrained_captures, constrained_captures_among};
 
 #[test]
 fn suffixless() {
@@ -67,3 +70,40 @@ fn raw_literal() {
     let captures = constrained_captures(&re, constraint, "");
     assert!(captures.is_none());
 }
+
+#[test]
+fn block_comment_candidates() {
+    #[rustfmt::skip]
+    let re = pretokeniser_regex(r##"\A
+        / \*
+        (?<comment_content>
+          . *
+        )
+        \* /
+        \z"##);
+
+    for haystack in [
+        "/**/",
+        "/* a */ b",
+        "/* /* */ */ */",
+        "/* */ /* */",
+        "/*/ */",
+        "/* /*/ /*/ */ */x",
+        "/** é */ £",
+        "/* /* */",
+        "/*",
+    ] {
+        let expected = constrained_captures(&re, block_comment_constraint, haystack);
+        let found = constrained_captures_among(
+            &re,
+            block_comment_constraint,
+            haystack,
+            block_comment_candidate_lengths(haystack),
+        );
+        assert_eq!(
+            expected.map(|captures| captures[0].to_owned()),
+            found.map(|captures| captures[0].to_owned()),
+            "{haystack}"
+        );
+    }
+}
@@ -9,6 +9,7 @@ use self::escape_processing::{
 };
 
 use super::pretokenisation::{Pretoken, PretokenData};
+use super::{RejectionCategory, Span};
 
 mod escape_processing;
 
@@ -27,6 +28,9 @@ pub struct FineToken {
 
     /// The input characters which make up the token.
     pub extent: Charseq,
+
+    /// The token's position in the input, as byte offsets.
+    pub span: Span,
 }
 
 /// A fine-grained token's kind and attributes.
@@ -46,9 +50,13 @@ pub enum FineTokenData {
     },
     Identifier {
         represented_identifier: Charseq,
+        /// The identifier as written, if that wasn't in NFC
+        original_identifier: Option<Charseq>,
     },
     RawIdentifier {
         represented_identifier: Charseq,
+        /// The identifier as written (without the `r#`), if that wasn't in NFC
+        original_identifier: Option<Charseq>,
     },
     LifetimeOrLabel {
         name: Charseq,
@@ -67,6 +75,8 @@ pub enum FineTokenData {
     StringLiteral {
         represented_string: Charseq,
         suffix: Charseq,
+        /// The string continuation escapes in the literal, in order.
+        continuations: Vec<StringContinuation>,
     },
     RawStringLiteral {
         represented_string: Charseq,
@@ -75,6 +85,8 @@ pub enum FineTokenData {
     ByteStringLiteral {
         represented_bytes: Vec<u8>,
         suffix: Charseq,
+        /// The string continuation escapes in the literal, in order.
+        continuations: Vec<StringContinuation>,
     },
     RawByteStringLiteral {
         represented_bytes: Vec<u8>,
@@ -83,6 +95,8 @@ pub enum FineTokenData {
     CStringLiteral {
         represented_bytes: Vec<u8>,
         suffix: Charseq,
+        /// The string continuation escapes in the literal, in order.
+        continuations: Vec<StringContinuation>,
     },
     RawCStringLiteral {
         represented_bytes: Vec<u8>,
@@ -92,13 +106,44 @@ pub enum FineTokenData {
         base: NumericBase,
         digits: Charseq,
         suffix: Charseq,
+        /// The value the digits represent, or None if it doesn't fit in a `u128`.
+        ///
+        /// Lexical analysis doesn't reject literals which are too large; that happens later.
+        represented_value: Option<u128>,
     },
     FloatLiteral {
         body: Charseq,
         suffix: Charseq,
+        /// The value the body represents.
+        represented_value: FloatValue,
     },
 }
 
+/// The value represented by a floating-point literal's body.
+///
+/// The body is interpreted as an `f32` if the literal's suffix is `f32`, and as an `f64` otherwise.
+#[derive(Copy, Clone, std::fmt::Debug)]
+pub enum FloatValue {
+    F32(f32),
+    F64(f64),
+}
+
+/// A string continuation escape (backslash-newline and the whitespace after it) in a string, byte
+/// string, or C string literal.
+///
+/// These don't affect the represented string, but rustc reports warnings about some of them.
+#[derive(Clone, std::fmt::Debug)]
+pub struct StringContinuation {
+    /// Byte offsets of the characters skipped by the escape, relative to the start of the token.
+    ///
+    /// This includes the backslash, the newline, and all the whitespace which followed it.
+    pub skipped: std::ops::Range<usize>,
+
+    /// Whether the skipped whitespace included a further newline (ie, the continuation skipped
+    /// blank lines).
+    pub skips_multiple_lines: bool,
+}
+
 /// Whether a comment is a doc-comment, and if so which sort of doc-comment.
 #[derive(Copy, Clone, std::fmt::Debug)]
 #[allow(clippy::enum_variant_names)]
@@ -117,6 +162,18 @@ pub enum NumericBase {
     Hexadecimal,
 }
 
+impl NumericBase {
+    /// Returns the base as a number (for example 16 for hexadecimal).
+    pub(crate) fn radix(self) -> u32 {
+        match self {
+            NumericBase::Binary => 2,
+            NumericBase::Octal => 8,
+            NumericBase::Decimal => 10,
+            NumericBase::Hexadecimal => 16,
+        }
+    }
+}
+
 impl FineTokenData {
     /// Says whether this token counts as whitespace.
     ///
@@ -146,11 +203,26 @@ impl FineTokenData {
 ///
 /// If the pretoken is accepted, returns a fine-grained token.
 ///
-/// If the pretoken is rejected, distinguishes rejection from "model error".
+/// If the pretoken is rejected, distinguishes rejection from "model error". A model error's message
+/// ends by describing the pretoken.
 pub fn reprocess(pretoken: &Pretoken) -> Result<FineToken, Error> {
-    let token_data = match &pretoken.data {
+    let token_data = reprocess_data(&pretoken.data).map_err(|e| match e {
+        Error::ModelError(message) => {
+            Error::ModelError(format!("{message}, reprocessing {:?}", pretoken.data))
+        }
+        e => e,
+    })?;
+    Ok(FineToken {
+        data: token_data,
+        extent: pretoken.extent.clone(),
+        span: pretoken.span,
+    })
+}
+
+fn reprocess_data(data: &PretokenData) -> Result<FineTokenData, Error> {
+    Ok(match data {
         PretokenData::Reserved => {
-            return Err(rejected("reserved form"));
+            return Err(rejected(RejectionCategory::ReservedForm, "reserved form"));
         }
         PretokenData::Whitespace => FineTokenData::Whitespace,
         PretokenData::LineComment { comment_content } => lex_line_comment(comment_content)?,
@@ -195,10 +267,6 @@ pub fn reprocess(pretoken: &Pretoken) -> Result<FineToken, Error> {
             exponent_digits,
             suffix,
         } => lex_float_literal(*has_base, body, exponent_digits, suffix)?,
-    };
-    Ok(FineToken {
-        data: token_data,
-        extent: pretoken.extent.clone(),
     })
 }
 
@@ -207,7 +275,7 @@ pub enum Error {
     /// Reprocessing rejected the pretoken.
     ///
     /// The string describes the reason for rejection.
-    Rejected(String),
+    Rejected(RejectionCategory, String),
 
     /// The input demonstrated a problem in lexlucid's model or implementation.
     ///
@@ -219,8 +287,45 @@ fn model_error(s: &str) -> Error {
     Error::ModelError(s.to_owned())
 }
 
-fn rejected(s: &str) -> Error {
-    Error::Rejected(s.to_owned())
+fn rejected(category: RejectionCategory, s: &str) -> Error {
+    Error::Rejected(category, s.to_owned())
+}
+
+/// Rejection for a character which may appear in a quoted literal only as an escape.
+fn rejected_escape_only_char(c: char) -> Error {
+    Error::Rejected(
+        RejectionCategory::EscapeOnlyChar,
+        format!("escape-only char: U+{:04X}", c as u32),
+    )
+}
+
+/// Describes why a raw string literal couldn't be lexed, if `rest` (the input from the start of a
+/// reserved form) looks like the start of one.
+///
+/// Returns `None` unless `rest` begins with a raw string prefix and opening quote which aren't
+/// matched by a suitable closing quote, in which case the description mentions the closing quote
+/// with the most hashes (if there is any closing quote at all).
+pub fn describe_raw_string_near_miss(rest: &str) -> Option<String> {
+    let after_prefix = ["br", "cr", "r"]
+        .iter()
+        .find_map(|prefix| rest.strip_prefix(prefix))?;
+    let body = after_prefix.trim_start_matches('#');
+    let opening = after_prefix.len() - body.len();
+    let body = body.strip_prefix('"')?;
+    let mut closing = None;
+    for (index, _) in body.match_indices('"') {
+        let hashes = body[index + 1..].len() - body[index + 1..].trim_start_matches('#').len();
+        if hashes >= opening {
+            return None;
+        }
+        closing = closing.max(Some(hashes));
+    }
+    Some(match closing {
+        Some(closing) => {
+            format!("raw string closing hashes ({closing}) fewer than opening ({opening})")
+        }
+        None => format!("unterminated raw string (no closing quote; opening hashes: {opening})"),
+    })
 }
 
 /// Validates and interprets a line comment.
@@ -232,8 +337,13 @@ fn lex_line_comment(comment_content: &Charseq) -> Result<FineTokenData, Error> {
         ['!', rest @ ..] => (CommentStyle::InnerDoc, rest),
         _ => (CommentStyle::NonDoc, &[] as &[char]),
     };
+    // rustc rejects a CR only in doc-comments (where it would end up in the doc attribute); a CR
+    // in a non-doc comment (including one beginning `////`) is accepted.
     if !matches!(style, CommentStyle::NonDoc) && comment_content.contains(&'\r') {
-        return Err(rejected("CR in line doc comment"));
+        return Err(rejected(
+            RejectionCategory::BareCr,
+            "CR in line doc comment",
+        ));
     }
     Ok(FineTokenData::LineComment {
         style,
@@ -244,6 +354,8 @@ fn lex_line_comment(comment_content: &Charseq) -> Result<FineTokenData, Error> {
 /// Validates and interprets a block comment.
 fn lex_block_comment(comment_content: &Charseq) -> Result<FineTokenData, Error> {
     let comment_content = comment_content.chars();
+    // Content beginning with two stars (as in `/***/`) isn't a doc-comment, and nor is content
+    // consisting of a single star (`/**/`). But `/*!*/` is an (empty) inner doc-comment.
     let (style, body) = match comment_content {
         ['*', '*', ..] => (CommentStyle::NonDoc, &[] as &[char]),
         ['*', rest @ ..] if !rest.is_empty() => (CommentStyle::OuterDoc, rest),
@@ -251,7 +363,10 @@ fn lex_block_comment(comment_content: &Charseq) -> Result<FineTokenData, Error>
         _ => (CommentStyle::NonDoc, &[] as &[char]),
     };
     if !matches!(style, CommentStyle::NonDoc) && comment_content.contains(&'\r') {
-        return Err(rejected("CR in block doc comment"));
+        return Err(rejected(
+            RejectionCategory::BareCr,
+            "CR in block doc comment",
+        ));
     }
     Ok(FineTokenData::BlockComment {
         style,
@@ -261,28 +376,45 @@ fn lex_block_comment(comment_content: &Charseq) -> Result<FineTokenData, Error>
 
 /// Validates and interprets a non-raw identifier.
 fn lex_nonraw_identifier(identifier: &Charseq) -> Result<FineTokenData, Error> {
+    let represented_identifier = identifier.nfc();
     Ok(FineTokenData::Identifier {
-        represented_identifier: identifier.nfc(),
+        original_identifier: original_if_normalised(identifier, &represented_identifier),
+        represented_identifier,
     })
 }
 
+/// Identifiers which can't be written in raw form (as identifiers or as lifetimes or labels).
+const FORBIDDEN_RAW_IDENTIFIERS: &[&str] = &["_", "crate", "self", "super", "Self"];
+
 /// Validates and interprets a `r#...` raw identifier.
 fn lex_raw_identifier(identifier: &Charseq) -> Result<FineTokenData, Error> {
     let represented_identifier = identifier.nfc();
     let s = represented_identifier.to_string();
-    if s == "_" || s == "crate" || s == "self" || s == "super" || s == "Self" {
-        return Err(rejected("forbidden raw identifier"));
+    if FORBIDDEN_RAW_IDENTIFIERS.contains(&s.as_str()) {
+        return Err(Error::Rejected(
+            RejectionCategory::ForbiddenRawIdentifier,
+            format!("forbidden raw identifier: {s}"),
+        ));
     }
     Ok(FineTokenData::RawIdentifier {
+        original_identifier: original_if_normalised(identifier, &represented_identifier),
         represented_identifier,
     })
 }
 
-/// Validates and interprets a `r#...` raw identifier.
+/// Returns the identifier as written if NFC normalisation changed it.
+fn original_if_normalised(identifier: &Charseq, normalised: &Charseq) -> Option<Charseq> {
+    (identifier != normalised).then(|| identifier.clone())
+}
+
+/// Validates and interprets a `'r#...` raw lifetime or label.
 fn lex_raw_lifetime_or_label(name: &Charseq) -> Result<FineTokenData, Error> {
     let s = name.to_string();
-    if s == "_" || s == "crate" || s == "self" || s == "super" || s == "Self" {
-        return Err(rejected("forbidden raw lifetime or label"));
+    if FORBIDDEN_RAW_IDENTIFIERS.contains(&s.as_str()) {
+        return Err(Error::Rejected(
+            RejectionCategory::ForbiddenRawIdentifier,
+            format!("forbidden raw lifetime or label: {s}"),
+        ));
     }
     Ok(FineTokenData::RawLifetimeOrLabel { name: name.clone() })
 }
@@ -294,7 +426,10 @@ fn lex_single_quote_literal(
     suffix: &Charseq,
 ) -> Result<FineTokenData, Error> {
     if suffix.chars() == ['_'] {
-        return Err(rejected("underscore literal suffix"));
+        return Err(rejected(
+            RejectionCategory::UnderscoreSuffix,
+            "underscore literal suffix",
+        ));
     }
     match *prefix.chars() {
         [] => Ok(FineTokenData::CharacterLiteral {
@@ -305,7 +440,7 @@ fn lex_single_quote_literal(
             represented_byte: unescape_single_quoted_byte(literal_content)?,
             suffix: suffix.clone(),
         }),
-        _ => Err(model_error("impossible prefix")),
+        _ => Err(Error::ModelError(format!("impossible prefix: {prefix:?}"))),
     }
 }
 
@@ -316,22 +451,48 @@ fn lex_nonraw_double_quote_literal(
     suffix: &Charseq,
 ) -> Result<FineTokenData, Error> {
     if suffix.chars() == ['_'] {
-        return Err(rejected("underscore literal suffix"));
-    }
+        return Err(rejected(
+            RejectionCategory::UnderscoreSuffix,
+            "underscore literal suffix",
+        ));
+    }
+    // The literal content comes after the prefix (which is ASCII) and the opening quote.
+    let content_offset = prefix.len() + 1;
+    let token_relative = |mut continuations: Vec<StringContinuation>| {
+        for continuation in continuations.iter_mut() {
+            continuation.skipped.start += content_offset;
+            continuation.skipped.end += content_offset;
+        }
+        continuations
+    };
     match *prefix.chars() {
-        [] => Ok(FineTokenData::StringLiteral {
-            represented_string: unescape_double_quoted_string(literal_content)?,
-            suffix: suffix.clone(),
-        }),
-        ['b'] => Ok(FineTokenData::ByteStringLiteral {
-            represented_bytes: unescape_double_quoted_byte_string(literal_content)?,
-            suffix: suffix.clone(),
-        }),
-        ['c'] => Ok(FineTokenData::CStringLiteral {
-            represented_bytes: unescape_c_string(literal_content)?,
-            suffix: suffix.clone(),
-        }),
-        _ => Err(model_error("impossible prefix")),
+        [] => {
+            let (represented_string, continuations) =
+                unescape_double_quoted_string(literal_content)?;
+            Ok(FineTokenData::StringLiteral {
+                represented_string,
+                suffix: suffix.clone(),
+                continuations: token_relative(continuations),
+            })
+        }
+        ['b'] => {
+            let (represented_bytes, continuations) =
+                unescape_double_quoted_byte_string(literal_content)?;
+            Ok(FineTokenData::ByteStringLiteral {
+                represented_bytes,
+                suffix: suffix.clone(),
+                continuations: token_relative(continuations),
+            })
+        }
+        ['c'] => {
+            let (represented_bytes, continuations) = unescape_c_string(literal_content)?;
+            Ok(FineTokenData::CStringLiteral {
+                represented_bytes,
+                suffix: suffix.clone(),
+                continuations: token_relative(continuations),
+            })
+        }
+        _ => Err(Error::ModelError(format!("impossible prefix: {prefix:?}"))),
     }
 }
 
@@ -342,7 +503,10 @@ fn lex_raw_double_quote_literal(
     suffix: &Charseq,
 ) -> Result<FineTokenData, Error> {
     if suffix.chars() == ['_'] {
-        return Err(rejected("underscore literal suffix"));
+        return Err(rejected(
+            RejectionCategory::UnderscoreSuffix,
+            "underscore literal suffix",
+        ));
     }
     match *prefix.chars() {
         ['r'] => Ok(FineTokenData::RawStringLiteral {
@@ -357,19 +521,20 @@ fn lex_raw_double_quote_literal(
             represented_bytes: interpret_raw_c_string(literal_content)?,
             suffix: suffix.clone(),
         }),
-        _ => Err(model_error("impossible prefix")),
+        _ => Err(Error::ModelError(format!("impossible prefix: {prefix:?}"))),
     }
 }
 
 /// Validates and interprets a decimal integer literal.
 fn lex_integer_decimal_literal(digits: &Charseq, suffix: &Charseq) -> Result<FineTokenData, Error> {
     if digits.iter().all(|c| *c == '_') {
-        return Err(rejected("no digits"));
+        return Err(rejected(RejectionCategory::NoDigits, "no digits"));
     }
     Ok(FineTokenData::IntegerLiteral {
         base: NumericBase::Decimal,
         digits: digits.clone(),
         suffix: suffix.clone(),
+        represented_value: interpret_integer_digits(NumericBase::Decimal, digits)?,
     })
 }
 
@@ -379,42 +544,45 @@ fn lex_integer_hexadecimal_literal(
     suffix: &Charseq,
 ) -> Result<FineTokenData, Error> {
     if digits.iter().all(|c| *c == '_') {
-        return Err(rejected("no digits"));
+        return Err(rejected(RejectionCategory::NoDigits, "no digits"));
     }
     Ok(FineTokenData::IntegerLiteral {
         base: NumericBase::Hexadecimal,
         digits: digits.clone(),
         suffix: suffix.clone(),
+        represented_value: interpret_integer_digits(NumericBase::Hexadecimal, digits)?,
     })
 }
 
 /// Validates and interprets an octal integer literal.
 fn lex_integer_octal_literal(digits: &Charseq, suffix: &Charseq) -> Result<FineTokenData, Error> {
     if digits.iter().all(|c| *c == '_') {
-        return Err(rejected("no digits"));
+        return Err(rejected(RejectionCategory::NoDigits, "no digits"));
     }
     if !digits.iter().all(|c| *c == '_' || (*c >= '0' && *c < '8')) {
-        return Err(rejected("invalid digit"));
+        return Err(rejected(RejectionCategory::InvalidDigit, "invalid digit"));
     }
     Ok(FineTokenData::IntegerLiteral {
         base: NumericBase::Octal,
         digits: digits.clone(),
         suffix: suffix.clone(),
+        represented_value: interpret_integer_digits(NumericBase::Octal, digits)?,
     })
 }
 
 /// Validates and interprets a binary integer literal.
 fn lex_integer_binary_literal(digits: &Charseq, suffix: &Charseq) -> Result<FineTokenData, Error> {
     if digits.iter().all(|c| *c == '_') {
-        return Err(rejected("no digits"));
+        return Err(rejected(RejectionCategory::NoDigits, "no digits"));
     }
     if !digits.iter().all(|c| *c == '_' || (*c >= '0' && *c < '2')) {
-        return Err(rejected("invalid digit"));
+        return Err(rejected(RejectionCategory::InvalidDigit, "invalid digit"));
     }
     Ok(FineTokenData::IntegerLiteral {
         base: NumericBase::Binary,
         digits: digits.clone(),
         suffix: suffix.clone(),
+        represented_value: interpret_integer_digits(NumericBase::Binary, digits)?,
     })
 }
 
@@ -426,20 +594,58 @@ fn lex_float_literal(
     suffix: &Charseq,
 ) -> Result<FineTokenData, Error> {
     if has_base {
-        return Err(rejected("unsupported base for float"));
+        return Err(rejected(
+            RejectionCategory::UnsupportedFloatBase,
+            "unsupported base for float",
+        ));
     }
     if let Some(digits) = exponent_digits {
         if digits.iter().all(|c| *c == '_') {
-            return Err(rejected("no digits in exponent"));
+            return Err(rejected(
+                RejectionCategory::NoDigits,
+                "no digits in exponent",
+            ));
         }
     }
 
     Ok(FineTokenData::FloatLiteral {
         body: body.clone(),
         suffix: suffix.clone(),
+        represented_value: interpret_float_body(body, suffix)?,
     })
 }
 
+/// Interprets the digits of an integer literal (which may include `_`).
+///
+/// Returns None if the value doesn't fit in a `u128`.
+fn interpret_integer_digits(base: NumericBase, digits: &Charseq) -> Result<Option<u128>, Error> {
+    let mut value: Option<u128> = Some(0);
+    for c in digits.iter().filter(|c| **c != '_') {
+        let Some(digit) = c.to_digit(base.radix()) else {
+            return Err(Error::ModelError(format!(
+                "impossible digit in base {} integer literal: {digits:?}",
+                base.radix()
+            )));
+        };
+        value = value
+            .and_then(|v| v.checked_mul(base.radix().into()))
+            .and_then(|v| v.checked_add(digit.into()));
+    }
+    Ok(value)
+}
+
+/// Interprets the body of a floating-point literal (which may include `_`).
+fn interpret_float_body(body: &Charseq, suffix: &Charseq) -> Result<FloatValue, Error> {
+    let digits: String = body.iter().filter(|c| **c != '_').collect();
+    let value = if suffix.chars() == ['f', '3', '2'] {
+        digits.parse().map(FloatValue::F32)
+    } else {
+        digits.parse().map(FloatValue::F64)
+    };
+    value
+        .map_err(|_| Error::ModelError(format!("impossible floating-point literal body: {body:?}")))
+}
+
 /// Validates and interprets the content of a '' literal.
 fn unescape_single_quoted_character(literal_content: &Charseq) -> Result<char, Error> {
     if literal_content.is_empty() {
@@ -459,22 +665,26 @@ fn unescape_single_quoted_character(literal_content: &Charseq) -> Result<char, E
             return interpret_unicode_escape(&rest[1..]);
         }
         if rest.len() != 1 {
-            return Err(rejected("unknown escape"));
+            return Err(rejected(RejectionCategory::UnknownEscape, "unknown escape"));
         }
         match interpret_simple_escape(rest[0]) {
             Ok(escaped_value) => return Ok(escaped_value),
-            Err(_) => return Err(rejected("unknown escape")),
+            Err(_) => return Err(rejected(RejectionCategory::UnknownEscape, "unknown escape")),
         }
     }
     if literal_content.len() != 1 {
-        return Err(model_error("impossible literal content: len != 1"));
+        return Err(Error::ModelError(format!(
+            "impossible literal content (len != 1): {literal_content:?}"
+        )));
     }
     let c = literal_content[0];
     if c == '\'' {
-        return Err(model_error("impossible literal content: '"));
+        return Err(Error::ModelError(format!(
+            "impossible literal content (unescaped '): {literal_content:?}"
+        )));
     }
     if c == '\n' || c == '\r' || c == '\t' {
-        return Err(rejected("escape-only char"));
+        return Err(rejected_escape_only_char(c));
     }
     Ok(c)
 }
@@ -495,41 +705,68 @@ fn unescape_single_quoted_byte(literal_content: &Charseq) -> Result<u8, Error> {
             return interpret_8_bit_escape_as_byte(&rest[1..]);
         }
         if rest.len() != 1 {
-            return Err(rejected("unknown escape"));
+            return Err(rejected(RejectionCategory::UnknownEscape, "unknown escape"));
         }
         match interpret_simple_escape_as_byte(rest[0]) {
             Ok(b) => return Ok(b),
-            Err(_) => return Err(rejected("unknown escape")),
+            Err(_) => return Err(rejected(RejectionCategory::UnknownEscape, "unknown escape")),
         }
     }
     if literal_content.len() != 1 {
-        return Err(model_error("impossible literal content: len != 1"));
+        return Err(Error::ModelError(format!(
+            "impossible literal content (len != 1): {literal_content:?}"
+        )));
     }
     let c = literal_content[0];
     if c == '\'' {
-        return Err(model_error("impossible literal content: '"));
+        return Err(Error::ModelError(format!(
+            "impossible literal content (unescaped '): {literal_content:?}"
+        )));
     }
     if c == '\n' || c == '\r' || c == '\t' {
-        return Err(rejected("escape-only char"));
+        return Err(rejected_escape_only_char(c));
     }
     if c as u32 > 127 {
-        return Err(rejected("non-ASCII character in byte literal"));
+        return Err(rejected(
+            RejectionCategory::NonAscii,
+            "non-ASCII character in byte literal",
+        ));
     }
     Ok(c.try_into().unwrap())
 }
 
 /// Validates and interprets the content of a "" literal.
-fn unescape_double_quoted_string(literal_content: &Charseq) -> Result<Charseq, Error> {
-    let mut chars = literal_content.iter().copied().peekable();
+///
+/// Also returns the string continuation escapes which were found, with offsets relative to the
+/// start of `literal_content`.
+fn unescape_double_quoted_string(
+    literal_content: &Charseq,
+) -> Result<(Charseq, Vec<StringContinuation>), Error> {
+    // Number of bytes taken from the iterator (including any character held by peek()).
+    let consumed = std::cell::Cell::new(0);
+    let mut chars = literal_content
+        .iter()
+        .copied()
+        .inspect(|c| consumed.set(consumed.get() + c.len_utf8()))
+        .peekable();
     let mut unescaped = Vec::new();
+    let mut continuations = Vec::new();
     while let Some(c) = chars.next() {
         match c {
-            '\\' => match chars.next().ok_or_else(|| model_error("empty escape"))? {
+            '\\' => match chars.next().ok_or_else(|| {
+                Error::ModelError(format!("empty escape at the end of {literal_content:?}"))
+            })? {
                 'x' => {
                     let digits: Vec<_> = (0..2).filter_map(|_| chars.next()).collect();
                     unescaped.push(interpret_7_bit_escape(&digits)?);
                 }
                 'u' => {
+                    if chars.peek() != Some(&'{') {
+                        return Err(rejected(
+                            RejectionCategory::InvalidUnicodeEscape,
+                            "unicode escape missing opening brace",
+                        ));
+                    }
                     let mut escape = Vec::new();
                     loop {
                         match chars.next() {
@@ -539,82 +776,120 @@ fn unescape_double_quoted_string(literal_content: &Charseq) -> Result<Charseq, E
                                     break;
                                 }
                             }
-                            None => return Err(rejected("unterminated unicode escape")),
+                            None => {
+                                return Err(rejected(
+                                    RejectionCategory::InvalidUnicodeEscape,
+                                    "unterminated unicode escape",
+                                ))
+                            }
                         }
                     }
                     unescaped.push(interpret_unicode_escape(&escape)?);
                 }
-                '\n' => {
-                    while let Some(c) = chars.peek() {
-                        if is_string_continuation_whitespace(*c) {
-                            chars.next();
-                        } else {
-                            break;
-                        }
-                    }
-                }
+                '\n' => continuations.push(skip_string_continuation(&mut chars, &consumed)),
                 c => match interpret_simple_escape(c) {
                     Ok(escaped_value) => unescaped.push(escaped_value),
-                    Err(_) => return Err(rejected("unknown escape")),
+                    Err(_) => {
+                        return Err(rejected(RejectionCategory::UnknownEscape, "unknown escape"))
+                    }
                 },
             },
-            '\r' => return Err(rejected("CR in string literal")),
+            '\r' => return Err(rejected(RejectionCategory::BareCr, "CR in string literal")),
             _ => unescaped.push(c),
         }
     }
-    Ok(Charseq::new(unescaped))
+    Ok((Charseq::new(unescaped), continuations))
 }
 
 /// Validates and interprets the content of a b"" literal.
-fn unescape_double_quoted_byte_string(literal_content: &Charseq) -> Result<Vec<u8>, Error> {
-    let mut chars = literal_content.iter().copied().peekable();
+///
+/// Also returns the string continuation escapes which were found, with offsets relative to the
+/// start of `literal_content`.
+fn unescape_double_quoted_byte_string(
+    literal_content: &Charseq,
+) -> Result<(Vec<u8>, Vec<StringContinuation>), Error> {
+    // Number of bytes taken from the iterator (including any character held by peek()).
+    let consumed = std::cell::Cell::new(0);
+    let mut chars = literal_content
+        .iter()
+        .copied()
+        .inspect(|c| consumed.set(consumed.get() + c.len_utf8()))
+        .peekable();
     let mut unescaped = Vec::new();
+    let mut continuations = Vec::new();
     while let Some(c) = chars.next() {
         match c {
-            '\\' => match chars.next().ok_or_else(|| model_error("empty escape"))? {
+            '\\' => match chars.next().ok_or_else(|| {
+                Error::ModelError(format!("empty escape at the end of {literal_content:?}"))
+            })? {
                 'x' => {
                     let digits: Vec<_> = (0..2).filter_map(|_| chars.next()).collect();
                     unescaped.push(interpret_8_bit_escape(&digits)?);
                 }
-                '\n' => {
-                    while let Some(c) = chars.peek() {
-                        if is_string_continuation_whitespace(*c) {
-                            chars.next();
-                        } else {
-                            break;
-                        }
-                    }
-                }
+                '\n' => continuations.push(skip_string_continuation(&mut chars, &consumed)),
                 c => match interpret_simple_escape(c) {
                     Ok(escaped_value) => unescaped.push(escaped_value),
-                    Err(_) => return Err(rejected("unknown escape")),
+                    Err(_) => {
+                        return Err(rejected(RejectionCategory::UnknownEscape, "unknown escape"))
+                    }
                 },
             },
-            '\r' => return Err(rejected("CR in byte string literal")),
+            '\r' => {
+                return Err(rejected(
+                    RejectionCategory::BareCr,
+                    "CR in byte string literal",
+                ))
+            }
             _ => {
                 if c as u32 > 127 {
-                    return Err(rejected("non-ASCII character in byte string literal"));
+                    return Err(rejected(
+                        RejectionCategory::NonAscii,
+                        "non-ASCII character in byte string literal",
+                    ));
                 }
                 unescaped.push(c)
             }
         }
     }
-    Ok(unescaped.iter().map(|c| (*c).try_into().unwrap()).collect())
+    Ok((
+        unescaped.iter().map(|c| (*c).try_into().unwrap()).collect(),
+        continuations,
+    ))
 }
 
 /// Validates and interprets the content of a c"" literal.
-fn unescape_c_string(literal_content: &Charseq) -> Result<Vec<u8>, Error> {
+///
+/// Also returns the string continuation escapes which were found, with offsets relative to the
+/// start of `literal_content`.
+fn unescape_c_string(
+    literal_content: &Charseq,
+) -> Result<(Vec<u8>, Vec<StringContinuation>), Error> {
     let mut buf = [0; 4];
-    let mut chars = literal_content.iter().copied().peekable();
+    // Number of bytes taken from the iterator (including any character held by peek()).
+    let consumed = std::cell::Cell::new(0);
+    let mut chars = literal_content
+        .iter()
+        .copied()
+        .inspect(|c| consumed.set(consumed.get() + c.len_utf8()))
+        .peekable();
     let mut unescaped = Vec::new();
+    let mut continuations = Vec::new();
     while let Some(c) = chars.next() {
         match c {
-            '\\' => match chars.next().ok_or_else(|| model_error("empty escape"))? {
+            '\\' => match chars.next().ok_or_else(|| {
+                Error::ModelError(format!("empty escape at the end of {literal_content:?}"))
+            })? {
                 'x' => {
                     let digits: Vec<_> = (0..2).filter_map(|_| chars.next()).collect();
                     unescaped.push(interpret_8_bit_escape_as_byte(&digits)?);
                 }
                 'u' => {
+                    if chars.peek() != Some(&'{') {
+                        return Err(rejected(
+                            RejectionCategory::InvalidUnicodeEscape,
+                            "unicode escape missing opening brace",
+                        ));
+                    }
                     let mut escape = Vec::new();
                     loop {
                         match chars.next() {
@@ -624,7 +899,12 @@ fn unescape_c_string(literal_content: &Charseq) -> Result<Vec<u8>, Error> {
                                     break;
                                 }
                             }
-                            None => return Err(rejected("unterminated unicode escape")),
+                            None => {
+                                return Err(rejected(
+                                    RejectionCategory::InvalidUnicodeEscape,
+                                    "unterminated unicode escape",
+                                ))
+                            }
                         }
                     }
                     unescaped.extend(
@@ -633,34 +913,65 @@ fn unescape_c_string(literal_content: &Charseq) -> Result<Vec<u8>, Error> {
                             .bytes(),
                     );
                 }
-                '\n' => {
-                    while let Some(c) = chars.peek() {
-                        if is_string_continuation_whitespace(*c) {
-                            chars.next();
-                        } else {
-                            break;
-                        }
-                    }
-                }
+                '\n' => continuations.push(skip_string_continuation(&mut chars, &consumed)),
                 c => match interpret_simple_escape_as_byte(c) {
                     Ok(escaped_value) => unescaped.push(escaped_value),
-                    Err(_) => return Err(rejected("unknown escape")),
+                    Err(_) => {
+                        return Err(rejected(RejectionCategory::UnknownEscape, "unknown escape"))
+                    }
                 },
             },
-            '\r' => return Err(rejected("CR in C string literal")),
+            '\r' => {
+                return Err(rejected(
+                    RejectionCategory::BareCr,
+                    "CR in C string literal",
+                ))
+            }
             _ => unescaped.extend(c.encode_utf8(&mut buf).bytes()),
         }
     }
     if unescaped.contains(&0) {
-        return Err(rejected("NUL in C string literal"));
+        return Err(rejected(
+            RejectionCategory::NulInCString,
+            "NUL in C string literal",
+        ));
+    }
+    Ok((unescaped, continuations))
+}
+
+/// Skips the whitespace following the backslash and newline of a string continuation escape, and
+/// returns a description of the escape.
+///
+/// `consumed` is the number of bytes taken from `chars` (including any character held by peek()),
+/// which is used to work out the escape's offsets.
+fn skip_string_continuation(
+    chars: &mut std::iter::Peekable<impl Iterator<Item = char>>,
+    consumed: &std::cell::Cell<usize>,
+) -> StringContinuation {
+    let start = consumed.get() - 2;
+    let mut skips_multiple_lines = false;
+    while let Some(c) = chars.peek() {
+        if is_string_continuation_whitespace(*c) {
+            skips_multiple_lines |= *c == '\n';
+            chars.next();
+        } else {
+            break;
+        }
+    }
+    let end = consumed.get() - chars.peek().map_or(0, |c| c.len_utf8());
+    StringContinuation {
+        skipped: start..end,
+        skips_multiple_lines,
     }
-    Ok(unescaped)
 }
 
 /// Validates the content of a r"" literal.
 fn interpret_raw_string(literal_content: &Charseq) -> Result<Charseq, Error> {
     if literal_content.contains(&'\r') {
-        return Err(rejected("CR in raw string literal"));
+        return Err(rejected(
+            RejectionCategory::BareCr,
+            "CR in raw string literal",
+        ));
     }
     Ok(literal_content.clone())
 }
@@ -673,9 +984,15 @@ fn interpret_raw_byte_string(literal_content: &Charseq) -> Result<Vec<u8>, Error
         .copied()
         .map(|c| {
             if c == '\r' {
-                Err(rejected("CR in raw byte string literal"))
+                Err(rejected(
+                    RejectionCategory::BareCr,
+                    "CR in raw byte string literal",
+                ))
             } else if c as u32 > 127 {
-                Err(rejected("non-ASCII character in raw byte string literal"))
+                Err(rejected(
+                    RejectionCategory::NonAscii,
+                    "non-ASCII character in raw byte string literal",
+                ))
             } else {
                 Ok(c.try_into().unwrap())
             }
@@ -686,11 +1003,17 @@ fn interpret_raw_byte_string(literal_content: &Charseq) -> Result<Vec<u8>, Error
 /// Validates and interprets the content of a cr"" literal.
 fn interpret_raw_c_string(literal_content: &Charseq) -> Result<Vec<u8>, Error> {
     if literal_content.contains(&'\r') {
-        return Err(rejected("CR in raw C string literal"));
+        return Err(rejected(
+            RejectionCategory::BareCr,
+            "CR in raw C string literal",
+        ));
     }
     let unescaped: Vec<u8> = literal_content.to_string().into();
     if unescaped.contains(&0) {
-        return Err(rejected("NUL in raw C string literal"));
+        return Err(rejected(
+            RejectionCategory::NulInCString,
+            "NUL in raw C string literal",
+        ));
     }
     Ok(unescaped)
 }
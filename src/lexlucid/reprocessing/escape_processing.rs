@@ -8,7 +8,7 @@
 
 use crate::char_sequences::Charseq;
 
-use super::{model_error, rejected, Error};
+use super::{rejected, Error, RejectionCategory};
 
 /// Processes a _simple escape_ sequence, returning a byte.
 ///
@@ -24,7 +24,10 @@ pub fn interpret_simple_escape_as_byte(c: char) -> Result<u8, Error> {
         '\'' => 0x27,
         '\\' => 0x5c,
         _ => {
-            return Err(rejected("not a simple escape"));
+            return Err(rejected(
+                RejectionCategory::UnknownEscape,
+                "not a simple escape",
+            ));
         }
     };
     Ok(represented_byte)
@@ -44,10 +47,14 @@ pub fn interpret_simple_escape(c: char) -> Result<char, Error> {
 /// a well-formed 8-bit escape.
 pub fn interpret_8_bit_escape_as_byte(digits: &[char]) -> Result<u8, Error> {
     if digits.len() != 2 {
-        return Err(rejected("invalid 8-bit escape"));
+        return Err(rejected(
+            RejectionCategory::InvalidHexEscape,
+            "invalid 8-bit escape",
+        ));
     }
     let digits: String = digits.iter().collect();
-    u8::from_str_radix(&digits, 16).map_err(|_| rejected("invalid 8-bit escape"))
+    u8::from_str_radix(&digits, 16)
+        .map_err(|_| rejected(RejectionCategory::InvalidHexEscape, "invalid 8-bit escape"))
 }
 
 /// Processes an _8-bit escape_ sequence, returning a char.
@@ -64,18 +71,27 @@ pub fn interpret_8_bit_escape(digits: &[char]) -> Result<char, Error> {
 /// escape.
 pub fn interpret_7_bit_escape(digits: &[char]) -> Result<char, Error> {
     if digits.len() != 2 {
-        return Err(rejected("invalid 7-bit escape"));
+        return Err(rejected(
+            RejectionCategory::InvalidHexEscape,
+            "invalid 7-bit escape",
+        ));
     }
     let digits: String = digits.iter().collect();
     match u8::from_str_radix(&digits, 16) {
         Ok(byte) => {
             if byte >= 0x80 {
-                Err(rejected("invalid 7-bit escape"))
+                Err(rejected(
+                    RejectionCategory::InvalidHexEscape,
+                    "invalid 7-bit escape",
+                ))
             } else {
                 Ok(byte.into())
             }
         }
-        Err(_) => Err(rejected("invalid 7-bit escape")),
+        Err(_) => Err(rejected(
+            RejectionCategory::InvalidHexEscape,
+            "invalid 7-bit escape",
+        )),
     }
 }
 
@@ -83,28 +99,67 @@ pub fn interpret_7_bit_escape(digits: &[char]) -> Result<char, Error> {
 ///
 /// Returns the escaped value, or rejects if **`\u`** followed by `escape` isn't a well-formed
 /// unicode escape.
+///
+/// Underscores may appear anywhere among the digits except at the start, and don't count towards
+/// the limit of six digits. A leading underscore and an escape with no digits are rejected with
+/// different messages.
 pub fn interpret_unicode_escape(escape: &[char]) -> Result<char, Error> {
-    let ['{', chars @ .., '}'] = escape else {
-        return Err(rejected("unbraced unicode escape"));
+    let ['{', rest @ ..] = escape else {
+        return Err(rejected(
+            RejectionCategory::InvalidUnicodeEscape,
+            "unicode escape missing opening brace",
+        ));
+    };
+    let [chars @ .., '}'] = rest else {
+        return Err(rejected(
+            RejectionCategory::InvalidUnicodeEscape,
+            "unterminated unicode escape",
+        ));
     };
     if let Some('_') = chars.first() {
-        return Err(rejected("leading underscore in unicode escape"));
+        return Err(rejected(
+            RejectionCategory::InvalidUnicodeEscape,
+            "leading underscore in unicode escape",
+        ));
     }
     let digits: Charseq = chars.iter().copied().filter(|c| *c != '_').collect();
     if digits.is_empty() {
-        return Err(rejected("empty unicode escape"));
+        return Err(rejected(
+            RejectionCategory::InvalidUnicodeEscape,
+            "empty unicode escape",
+        ));
     }
     if digits.len() > 6 {
-        return Err(rejected("overlong unicode escape"));
+        return Err(rejected(
+            RejectionCategory::InvalidUnicodeEscape,
+            "overlong unicode escape",
+        ));
     }
     if !&digits.iter().all(|c| c.is_ascii_hexdigit()) {
-        return Err(rejected("invalid char in unicode escape"));
+        return Err(rejected(
+            RejectionCategory::InvalidUnicodeEscape,
+            "invalid char in unicode escape",
+        ));
     }
     match u32::from_str_radix(&digits.to_string(), 16) {
-        Ok(scalar_value) => {
-            char::from_u32(scalar_value).ok_or_else(|| rejected("invalid unicode escape"))
-        }
-        Err(_) => Err(model_error("unhandled invalid hex")),
+        Ok(0xD800..=0xDFFF) => Err(rejected(
+            RejectionCategory::InvalidUnicodeEscape,
+            "unicode escape for a surrogate",
+        )),
+        Ok(0x110000..) => Err(rejected(
+            RejectionCategory::InvalidUnicodeEscape,
+            "out of range unicode escape",
+        )),
+        Ok(scalar_value) => char::from_u32(scalar_value).ok_or_else(|| {
+            Error::ModelError(format!(
+                "unhandled invalid unicode scalar value in unicode escape {:?}",
+                Charseq::from(escape)
+            ))
+        }),
+        Err(_) => Err(Error::ModelError(format!(
+            "unhandled invalid hex in unicode escape {:?}",
+            Charseq::from(escape)
+        ))),
     }
 }
 
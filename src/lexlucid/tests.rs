@@ -0,0 +1,540 @@
+use crate::Edition;
+
+use super::pretokenisation::{PretokenData, RuleName};
+use super::reprocessing;
+use super::{
+    analyse, lex_one_token, with_trailing_trivia, Analysis, FineTokenData, RejectionCategory,
+    TokenOutcome,
+};
+
+/// A token's extent, and the start and end of its span.
+type ExtentAndSpan = (String, usize, usize);
+
+/// Lexes the input one token at a time, returning each token's extent and its span relative to
+/// the start of the whole input.
+///
+/// If lexing is rejected, returns the category and the first line of the description.
+fn lex_token_by_token(input: &str) -> Result<Vec<ExtentAndSpan>, (RejectionCategory, String)> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut index = 0;
+    let mut byte_index = 0;
+    let mut tokens = Vec::new();
+    loop {
+        match lex_one_token(&chars[index..], Edition::E2021) {
+            TokenOutcome::Lexed(token, consumed) => {
+                tokens.push((
+                    token.extent.to_string(),
+                    byte_index + token.span.start,
+                    byte_index + token.span.end,
+                ));
+                index += consumed;
+                byte_index += token.span.end;
+            }
+            TokenOutcome::EndOfInput => return Ok(tokens),
+            TokenOutcome::Rejects(category, reason) => {
+                return Err((category, reason.into_description().remove(0)))
+            }
+            TokenOutcome::ModelError(_) => panic!("model error"),
+        }
+    }
+}
+
+#[test]
+fn token_by_token_matches_analyse() {
+    for input in [
+        "",
+        "fn main() { let x = 1; }",
+        "r#\"£\"# /* a /* b */ */ 'a' 'é",
+        "c\"x\" 1.5e3f64 /// doc\n",
+    ] {
+        let Analysis::Accepts(_, tokens) = analyse(input, Edition::E2021) else {
+            panic!("{input} should be accepted");
+        };
+        let expected: Vec<_> = tokens
+            .iter()
+            .map(|token| (token.extent.to_string(), token.span.start, token.span.end))
+            .collect();
+        assert_eq!(lex_token_by_token(input).unwrap(), expected, "{input}");
+    }
+}
+
+#[test]
+fn token_by_token_stops_at_rejection() {
+    assert_eq!(
+        lex_token_by_token("a \"\\q\" b").map_err(|(category, _)| category),
+        Err(RejectionCategory::UnknownEscape)
+    );
+}
+
+#[test]
+fn token_by_token_rejects_like_analyse() {
+    for input in [
+        "a \"\\q\" b",
+        "x r#\"abc\" y",
+        "br##\"abc\"# y",
+        "cr\"abc",
+        "x € y",
+        "1.0e",
+    ] {
+        let Analysis::Rejects(category, reason) = analyse(input, Edition::E2021) else {
+            panic!("{input} should be rejected");
+        };
+        let expected = (category, reason.into_description().remove(0));
+        assert_eq!(lex_token_by_token(input), Err(expected), "{input}");
+    }
+}
+
+/// Lexes the input, returning the extents of the tokens, or the category of rejection.
+fn accepted_extents(input: &str, edition: Edition) -> Result<Vec<String>, RejectionCategory> {
+    match analyse(input, edition) {
+        Analysis::Accepts(_, tokens) => Ok(tokens
+            .iter()
+            .map(|token| token.extent.to_string())
+            .collect()),
+        Analysis::Rejects(category, _) => Err(category),
+        Analysis::ModelError(_) => panic!("model error"),
+    }
+}
+
+#[test]
+fn lifetime_hash_forms_in_each_edition() {
+    assert_eq!(
+        accepted_extents("'r#foo", Edition::E2015),
+        Ok(vec!["'r".into(), "#".into(), "foo".into()])
+    );
+    assert_eq!(
+        accepted_extents("'foo#", Edition::E2015),
+        Ok(vec!["'foo".into(), "#".into()])
+    );
+    for edition in [Edition::E2021, Edition::E2024] {
+        assert_eq!(
+            accepted_extents("'r#foo", edition),
+            Ok(vec!["'r#foo".into()])
+        );
+        assert_eq!(
+            accepted_extents("'foo#", edition),
+            Err(RejectionCategory::ReservedForm)
+        );
+    }
+}
+
+#[test]
+fn c_string_prefixes_in_each_edition() {
+    assert_eq!(
+        accepted_extents("c\"x\"", Edition::E2015),
+        Ok(vec!["c".into(), "\"x\"".into()])
+    );
+    assert_eq!(
+        accepted_extents("cr#\"x\"#", Edition::E2015),
+        Ok(vec!["cr".into(), "#".into(), "\"x\"".into(), "#".into()])
+    );
+    for edition in [Edition::E2021, Edition::E2024] {
+        assert_eq!(
+            accepted_extents("c\"x\"", edition),
+            Ok(vec!["c\"x\"".into()])
+        );
+        assert_eq!(
+            accepted_extents("cr#\"x\"#", edition),
+            Ok(vec!["cr#\"x\"#".into()])
+        );
+    }
+}
+
+#[test]
+fn nul_in_each_string_family() {
+    let only_token = |input: &str| match analyse(input, Edition::E2021) {
+        Analysis::Accepts(_, mut tokens) if tokens.len() == 1 => Ok(tokens.remove(0).data),
+        Analysis::Rejects(category, _) => Err(category),
+        _ => panic!("{input} should give a single token or be rejected"),
+    };
+    let Ok(FineTokenData::StringLiteral {
+        represented_string, ..
+    }) = only_token(r#""\0""#)
+    else {
+        panic!("string");
+    };
+    assert_eq!(represented_string.chars(), ['\0']);
+    let Ok(FineTokenData::ByteStringLiteral {
+        represented_bytes, ..
+    }) = only_token(r#"b"\0""#)
+    else {
+        panic!("byte string");
+    };
+    assert_eq!(represented_bytes, [0]);
+    let Ok(FineTokenData::RawStringLiteral {
+        represented_string, ..
+    }) = only_token(r#"r"\0""#)
+    else {
+        panic!("raw string");
+    };
+    assert_eq!(represented_string.chars(), ['\\', '0']);
+    for input in [
+        r#"c"\0""#,
+        r#"c"\x00""#,
+        r#"c"\u{0}""#,
+        "c\"\0\"",
+        "cr\"\0\"",
+    ] {
+        assert!(
+            matches!(only_token(input), Err(RejectionCategory::NulInCString)),
+            "{input:?}"
+        );
+    }
+}
+
+#[test]
+fn cr_is_rejected_only_in_doc_comments() {
+    for input in ["//a\rb", "////a\rb", "//a\r", "/*a\rb*/", "/***a\rb*/"] {
+        assert!(accepted_extents(input, Edition::E2021).is_ok(), "{input:?}");
+    }
+    for input in ["///a\rb", "//!a\rb", "///\r", "/**a\rb*/", "/*!a\rb*/"] {
+        assert_eq!(
+            accepted_extents(input, Edition::E2021),
+            Err(RejectionCategory::BareCr),
+            "{input:?}"
+        );
+    }
+}
+
+#[test]
+fn trailing_trivia_reconstructs_input() {
+    for input in [
+        "",
+        "  \n",
+        "// leading\nfn f() /* x */ {}  // end",
+        "/// doc\n//! inner\nx /** block doc */ y\n",
+    ] {
+        let Analysis::Accepts(_, tokens) = analyse(input, Edition::E2021) else {
+            panic!("{input} should be accepted");
+        };
+        let grouped = with_trailing_trivia(tokens);
+        let mut reconstructed = grouped.leading_trivia().to_string();
+        for (token, trailing) in grouped {
+            assert!(
+                !token.data.is_whitespace(),
+                "{input:?}: trivia yielded as a token"
+            );
+            reconstructed.push_str(&token.extent.to_string());
+            reconstructed.push_str(&trailing.to_string());
+        }
+        assert_eq!(reconstructed, input);
+    }
+}
+
+#[test]
+fn doc_comments_are_not_trivia() {
+    let Analysis::Accepts(_, tokens) = analyse("a /// doc\n b", Edition::E2021) else {
+        panic!("should be accepted");
+    };
+    let grouped: Vec<(String, String)> = with_trailing_trivia(tokens)
+        .map(|(token, trailing)| (token.extent.to_string(), trailing.to_string()))
+        .collect();
+    assert_eq!(
+        grouped,
+        [
+            ("a".into(), " ".into()),
+            ("/// doc".into(), "\n ".into()),
+            ("b".into(), "".into()),
+        ]
+    );
+}
+
+#[test]
+fn based_floats_are_rejected_but_field_access_is_not() {
+    for input in [
+        "0x1.0", "0b1.0", "0o1.0", "0x1.", "0b1e2", "0o7.0f32", "0x1.2.3",
+    ] {
+        assert_eq!(
+            accepted_extents(input, Edition::E2021),
+            Err(RejectionCategory::UnsupportedFloatBase),
+            "{input}"
+        );
+    }
+    assert_eq!(
+        accepted_extents("0x1.foo", Edition::E2021).unwrap(),
+        ["0x1", ".", "foo"]
+    );
+    assert_eq!(
+        accepted_extents("0x1p4", Edition::E2021).unwrap(),
+        ["0x1p4"]
+    );
+    assert_eq!(
+        accepted_extents("0x1..", Edition::E2021).unwrap(),
+        ["0x1", ".", "."]
+    );
+}
+
+#[test]
+fn raw_string_near_miss_messages() {
+    let rejection_message = |input| match analyse(input, Edition::E2021) {
+        Analysis::Rejects(category, reason) => {
+            assert_eq!(category, RejectionCategory::ReservedForm, "{input}");
+            reason.into_description().remove(0)
+        }
+        _ => panic!("{input} should be rejected"),
+    };
+    assert_eq!(
+        rejection_message(r###"r##"x"#"###),
+        "raw string closing hashes (1) fewer than opening (2)"
+    );
+    assert_eq!(
+        rejection_message(r###"br###"a"b"## "###),
+        "raw string closing hashes (2) fewer than opening (3)"
+    );
+    assert_eq!(
+        rejection_message(r##"r#"x"##),
+        "unterminated raw string (no closing quote; opening hashes: 1)"
+    );
+    assert_eq!(rejection_message("x##"), "reserved form");
+}
+
+#[test]
+fn unicode_escape_without_opening_brace() {
+    let rejection_message = |input| match analyse(input, Edition::E2021) {
+        Analysis::Rejects(category, reason) => {
+            assert_eq!(category, RejectionCategory::InvalidUnicodeEscape, "{input}");
+            reason.into_description().remove(0)
+        }
+        _ => panic!("{input} should be rejected"),
+    };
+    for input in [
+        r#""\u""#,
+        r#""\uABCD""#,
+        r#""\uABCD}""#,
+        r"'\u41'",
+        r#"c"\u41}""#,
+    ] {
+        assert_eq!(
+            rejection_message(input),
+            "unicode escape missing opening brace",
+            "{input}"
+        );
+    }
+    for input in [r#""\u{""#, r"'\u{41'", r#"c"\u{41""#] {
+        assert_eq!(
+            rejection_message(input),
+            "unterminated unicode escape",
+            "{input}"
+        );
+    }
+}
+
+#[test]
+fn float_suffixes_beginning_with_e() {
+    for input in ["1.0e", "1.0_e", "1.0e+", "1.0E-_", "1.0ef32"] {
+        assert_eq!(
+            accepted_extents(input, Edition::E2021),
+            Err(RejectionCategory::NoDigits),
+            "{input}"
+        );
+    }
+    for input in ["1.0f32", "1.0e3f64", "1e3e", "1.0_f32", "1.0e3_f64"] {
+        assert_eq!(
+            accepted_extents(input, Edition::E2021),
+            Ok(vec![input.to_owned()]),
+            "{input}"
+        );
+    }
+}
+
+#[test]
+fn pretokens_record_their_rule() {
+    let Analysis::Accepts(pretokens, _) = analyse("r#x 'a 1e3", Edition::E2021) else {
+        panic!("should be accepted");
+    };
+    let rules: Vec<_> = pretokens.iter().map(|pretoken| pretoken.rule).collect();
+    assert_eq!(
+        rules,
+        [
+            RuleName::RawIdentifier,
+            RuleName::Whitespace,
+            RuleName::NonRawLifetimeOrLabel,
+            RuleName::Whitespace,
+            RuleName::FloatLiteralWithExponent,
+        ]
+    );
+}
+
+#[test]
+fn whitespace_controls_in_raw_strings_and_doc_comments() {
+    let only_token = |input: &str| match analyse(input, Edition::E2021) {
+        Analysis::Accepts(_, mut tokens) if tokens.len() == 1 => tokens.remove(0).data,
+        _ => panic!("{input} should give a single token"),
+    };
+    let FineTokenData::RawStringLiteral {
+        represented_string, ..
+    } = only_token("r#\"a\tb\x0cc\x0bd\"#")
+    else {
+        panic!("raw string");
+    };
+    assert_eq!(represented_string.to_string(), "a\tb\x0cc\x0bd");
+    let FineTokenData::RawByteStringLiteral {
+        represented_bytes, ..
+    } = only_token("br\"\t\x0c\x0b\"")
+    else {
+        panic!("raw byte string");
+    };
+    assert_eq!(represented_bytes, b"\t\x0c\x0b");
+    let FineTokenData::RawCStringLiteral {
+        represented_bytes, ..
+    } = only_token("cr\"\t\x0c\x0b\"")
+    else {
+        panic!("raw C string");
+    };
+    assert_eq!(represented_bytes, b"\t\x0c\x0b");
+    let FineTokenData::LineComment { body, .. } = only_token("/// a\tb\x0cc\x0bd") else {
+        panic!("line doc comment");
+    };
+    assert_eq!(body.to_string(), " a\tb\x0cc\x0bd");
+}
+
+#[test]
+fn c_string_bytes_have_no_terminating_nul() {
+    for input in [r#"c"""#, r#"cr"""#, r#"c"x""#, r##"cr#"x"#"##] {
+        let Analysis::Accepts(_, tokens) = analyse(input, Edition::E2021) else {
+            panic!("{input} should be accepted");
+        };
+        let (FineTokenData::CStringLiteral {
+            represented_bytes, ..
+        }
+        | FineTokenData::RawCStringLiteral {
+            represented_bytes, ..
+        }) = &tokens[0].data
+        else {
+            panic!("{input} should be a C string literal");
+        };
+        assert_ne!(represented_bytes.last(), Some(&0), "{input}");
+    }
+}
+
+#[test]
+fn character_literals_and_lifetimes_sharing_a_prefix() {
+    for edition in Edition::ALL {
+        for (input, expected) in [
+            ("'a'", Ok(vec!["'a'"])),
+            ("'a", Ok(vec!["'a"])),
+            ("'a'b", Ok(vec!["'a'b"])),
+            ("'ab'", Err(RejectionCategory::NoRuleMatched)),
+            ("'_'", Ok(vec!["'_'"])),
+            ("'_", Ok(vec!["'_"])),
+            ("'漢'", Ok(vec!["'漢'"])),
+            ("'漢", Ok(vec!["'漢"])),
+            ("'a''b", Ok(vec!["'a'", "'b"])),
+            ("'a:'b", Ok(vec!["'a", ":", "'b"])),
+        ] {
+            assert_eq!(
+                accepted_extents(input, edition),
+                expected.map(|extents| extents.into_iter().map(String::from).collect()),
+                "{input} ({edition:?})"
+            );
+        }
+    }
+}
+
+#[test]
+fn whitespace_is_exactly_pattern_white_space() {
+    for c in [
+        '\u{b}', '\u{c}', '\u{85}', '\u{200e}', '\u{200f}', '\u{2028}', '\u{2029}',
+    ] {
+        let input = format!("a{c}b");
+        assert_eq!(
+            accepted_extents(&input, Edition::E2021),
+            Ok(vec!["a".into(), c.to_string(), "b".into()]),
+            "{c:?}"
+        );
+    }
+    for c in [
+        '\u{a0}', '\u{1680}', '\u{2000}', '\u{200a}', '\u{202f}', '\u{205f}', '\u{3000}',
+        '\u{180e}', '\u{200b}', '\u{1c}',
+    ] {
+        let input = format!("a{c}b");
+        assert_eq!(
+            accepted_extents(&input, Edition::E2021),
+            Err(RejectionCategory::NoRuleMatched),
+            "{c:?}"
+        );
+    }
+}
+
+#[test]
+fn reserved_hash_forms_in_each_edition() {
+    for edition in Edition::ALL {
+        let reserved_from_2024 = |extents: &[&str]| {
+            if edition == Edition::E2024 {
+                Err(RejectionCategory::ReservedForm)
+            } else {
+                Ok(extents.iter().map(|s| s.to_string()).collect())
+            }
+        };
+        for (input, expected) in [
+            (r#"#"x""#, reserved_from_2024(&["#", r#""x""#])),
+            (r##"#"x"#"##, reserved_from_2024(&["#", r#""x""#, "#"])),
+            (
+                r###"##"x"##"###,
+                reserved_from_2024(&["#", "#", r#""x""#, "#", "#"]),
+            ),
+            ("##", reserved_from_2024(&["#", "#"])),
+            ("#foo", Ok(vec!["#".into(), "foo".into()])),
+            ("# #", Ok(vec!["#".into(), " ".into(), "#".into()])),
+            (
+                r##"#r"x"#"##,
+                Ok(vec!["#".into(), r#"r"x""#.into(), "#".into()]),
+            ),
+        ] {
+            assert_eq!(
+                accepted_extents(input, edition),
+                expected,
+                "{input} ({edition:?})"
+            );
+        }
+    }
+}
+
+#[test]
+fn string_continuations_are_recorded_for_each_string_kind() {
+    for input in [
+        "\"ab\\\n\n x\\\ny\"",
+        "b\"a\\\n\n x\\\ny\"",
+        "c\"\\\n\n x\\\ny\"",
+    ] {
+        let Analysis::Accepts(_, tokens) = analyse(input, Edition::E2021) else {
+            panic!("{input} should be accepted");
+        };
+        let (FineTokenData::StringLiteral { continuations, .. }
+        | FineTokenData::ByteStringLiteral { continuations, .. }
+        | FineTokenData::CStringLiteral { continuations, .. }) = &tokens[0].data
+        else {
+            panic!("{input} should be a non-raw string literal");
+        };
+        let skipped: Vec<_> = continuations
+            .iter()
+            .map(|continuation| {
+                (
+                    &input[continuation.skipped.clone()],
+                    continuation.skips_multiple_lines,
+                )
+            })
+            .collect();
+        assert_eq!(skipped, [("\\\n\n ", true), ("\\\n", false)], "{input}");
+    }
+}
+
+#[test]
+fn model_errors_describe_the_content_and_pretoken() {
+    let Analysis::Accepts(mut pretokens, _) = analyse("b'x'", Edition::E2021) else {
+        panic!("b'x' wasn't accepted");
+    };
+    let mut pretoken = pretokens.remove(0);
+    let PretokenData::SingleQuoteLiteral { prefix, .. } = &mut pretoken.data else {
+        panic!("unexpected pretoken: {pretoken:?}");
+    };
+    *prefix = "z".into();
+    let Err(reprocessing::Error::ModelError(message)) = reprocessing::reprocess(&pretoken) else {
+        panic!("an impossible prefix wasn't reported as a model error");
+    };
+    assert_eq!(
+        message,
+        "impossible prefix: «z», reprocessing \
+         SingleQuoteLiteral { prefix: «z», literal_content: «x», suffix: «» }"
+    );
+}
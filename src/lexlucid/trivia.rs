@@ -0,0 +1,61 @@
+//! Grouping whitespace and non-doc comments with the tokens they follow.
+
+use std::iter::Peekable;
+
+use crate::char_sequences::Charseq;
+
+use super::FineToken;
+
+/// Iterator adaptor returned by [`with_trailing_trivia`].
+pub struct WithTrailingTrivia<I: Iterator<Item = FineToken>> {
+    tokens: Peekable<I>,
+    leading: Charseq,
+}
+
+/// Groups a sequence of fine-grained tokens so that each significant token carries the trivia
+/// (whitespace and non-doc comments) which follows it.
+///
+/// The adaptor yields each significant token together with the concatenated extents of the trivia
+/// tokens immediately after it. Trivia before the first significant token is available from
+/// [`WithTrailingTrivia::leading_trivia`].
+///
+/// Concatenating the leading trivia and then each token's extent followed by its trailing trivia
+/// reproduces the input exactly.
+pub fn with_trailing_trivia<I>(tokens: I) -> WithTrailingTrivia<I::IntoIter>
+where
+    I: IntoIterator<Item = FineToken>,
+{
+    let mut tokens = tokens.into_iter().peekable();
+    let leading = take_trivia(&mut tokens);
+    WithTrailingTrivia { tokens, leading }
+}
+
+impl<I: Iterator<Item = FineToken>> WithTrailingTrivia<I> {
+    /// Returns the trivia which appeared before the first significant token.
+    ///
+    /// If the input was all trivia, this is the whole input.
+    pub fn leading_trivia(&self) -> &Charseq {
+        &self.leading
+    }
+}
+
+impl<I: Iterator<Item = FineToken>> Iterator for WithTrailingTrivia<I> {
+    type Item = (FineToken, Charseq);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = self.tokens.next()?;
+        let trailing = take_trivia(&mut self.tokens);
+        Some((token, trailing))
+    }
+}
+
+/// Consumes trivia tokens from the front of `tokens`, returning their concatenated extents.
+fn take_trivia(tokens: &mut Peekable<impl Iterator<Item = FineToken>>) -> Charseq {
+    let mut trivia = Charseq::new(Vec::new());
+    while let Some(token) = tokens.next_if(|token| token.data.is_whitespace()) {
+        for c in token.extent.iter() {
+            trivia.push_char(*c);
+        }
+    }
+    trivia
+}
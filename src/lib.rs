@@ -0,0 +1,136 @@
+//! A Rust reimplementation of the lexer described in the writeup ("lexlucid"), and a harness for
+//! comparing it with rustc's lexer.
+//!
+//! The library interface is [`lex`], which runs lexlucid's analysis on a string.
+//! [`lex_one_token`] lexes a single token, for callers which want to stop early.
+//! [`with_trailing_trivia`] groups [`lex`]'s output so that each significant token carries the
+//! whitespace and comments which follow it.
+//!
+//! The comparison harness (and the command-line interface) requires the `rustc-harness` feature,
+//! which is enabled by default. It needs a nightly compiler with the `rustc-dev` component. Without
+//! that feature this crate builds on stable Rust.
+
+#![cfg_attr(feature = "rustc-harness", feature(rustc_private))]
+
+mod char_sequences;
+mod cleaning;
+mod lexlucid;
+mod utils;
+
+#[cfg(feature = "rustc-harness")]
+mod combination;
+#[cfg(feature = "rustc-harness")]
+mod command_line;
+#[cfg(feature = "rustc-harness")]
+mod comparison;
+#[cfg(feature = "rustc-harness")]
+mod confusables;
+#[cfg(feature = "rustc-harness")]
+mod coverage;
+#[cfg(feature = "rustc-harness")]
+mod lex_via_rustc;
+#[cfg(feature = "rustc-harness")]
+mod proptesting;
+#[cfg(feature = "rustc-harness")]
+mod ra_kinds;
+#[cfg(feature = "rustc-harness")]
+mod regular_tokens;
+#[cfg(feature = "rustc-harness")]
+mod rustc_recording;
+#[cfg(feature = "rustc-harness")]
+mod serialization;
+#[cfg(feature = "rustc-harness")]
+mod sexpr;
+#[cfg(feature = "rustc-harness")]
+mod simple_reports;
+#[cfg(feature = "rustc-harness")]
+mod testcases;
+
+pub use char_sequences::Charseq;
+pub use lexlucid::{
+    with_trailing_trivia, CommentStyle, FineToken, FineTokenData, FloatValue, NumericBase, Span,
+    StringContinuation, WithTrailingTrivia,
+};
+
+#[cfg(feature = "rustc-harness")]
+#[doc(hidden)]
+pub use command_line::run_cli;
+
+/// A Rust edition, as far as lexical analysis is concerned.
+///
+/// Editions which lex identically share a variant.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Edition {
+    /// Rust 2015 and Rust 2018
+    E2015,
+    /// Rust 2021
+    E2021,
+    /// Rust 2024
+    E2024,
+}
+
+impl Edition {
+    /// All the editions, oldest first.
+    pub const ALL: [Edition; 3] = [Edition::E2015, Edition::E2021, Edition::E2024];
+}
+
+/// Why [`lex`] didn't return any tokens.
+#[derive(Debug)]
+pub enum RejectionReason {
+    /// lexlucid rejected the input.
+    ///
+    /// The strings describe the reason for rejection, one string per line.
+    Rejected(Vec<String>),
+
+    /// The input demonstrated a problem in lexlucid's model or implementation.
+    ///
+    /// The strings describe the problem, one string per line.
+    ModelError(Vec<String>),
+}
+
+/// Runs lexlucid's lexical analysis on `input`, returning fine-grained tokens.
+///
+/// The input is cleaned first (removing a byte order mark or shebang, and converting CRLF to LF),
+/// so the tokens' extents and spans are taken from the cleaned input rather than from `input` (see
+/// [`Span`] for how to relate them to the original input).
+///
+/// The returned tokens include whitespace and comments.
+pub fn lex(input: &str, edition: Edition) -> Result<Vec<FineToken>, RejectionReason> {
+    let cleaned = cleaning::clean(input);
+    match lexlucid::analyse(&cleaned, edition) {
+        lexlucid::Analysis::Accepts(_, tokens) => Ok(tokens),
+        lexlucid::Analysis::Rejects(_, reason) => {
+            Err(RejectionReason::Rejected(reason.into_description()))
+        }
+        lexlucid::Analysis::ModelError(reason) => {
+            Err(RejectionReason::ModelError(reason.into_description()))
+        }
+    }
+}
+
+/// Runs lexlucid's lexical analysis on the start of `input`, returning the first token and the
+/// number of characters it was made from.
+///
+/// This lets a caller drive the lexer one token at a time, passing the rest of the input (after
+/// the returned number of characters) in the next call, and stop whenever it likes. This gives the
+/// same tokens as [`lex`], except that each token's span is relative to the start of the `input`
+/// passed in the call which returned it.
+///
+/// Unlike [`lex`], this doesn't clean the input.
+///
+/// Returns `Ok(None)` if the input is empty.
+pub fn lex_one_token(
+    input: &[char],
+    edition: Edition,
+) -> Result<Option<(FineToken, usize)>, RejectionReason> {
+    match lexlucid::lex_one_token(input, edition) {
+        lexlucid::TokenOutcome::Lexed(token, consumed) => Ok(Some((token, consumed))),
+        lexlucid::TokenOutcome::EndOfInput => Ok(None),
+        lexlucid::TokenOutcome::Rejects(_, reason) => {
+            Err(RejectionReason::Rejected(reason.into_description()))
+        }
+        lexlucid::TokenOutcome::ModelError(reason) => {
+            Err(RejectionReason::ModelError(reason.into_description()))
+        }
+    }
+}
@@ -1,14 +1,17 @@
 //! Uses `proptest` to compare the two lexer implementations.
 
+use std::hash::{BuildHasher, RandomState};
+
 use proptest::{
     strategy::{BoxedStrategy, Strategy},
-    test_runner::{Config, TestCaseError, TestError, TestRunner},
+    test_runner::{Config, RngAlgorithm, TestCaseError, TestError, TestRng, TestRunner},
 };
 
 use crate::Edition;
 use crate::{
     comparison::{compare, regularised_from_lexlucid, regularised_from_rustc, Comparison},
-    utils::escape_for_display,
+    lexlucid,
+    utils::{escape_for_display, rust_string_literal},
 };
 
 pub use self::strategies::DEFAULT_STRATEGY;
@@ -17,20 +20,45 @@ use self::strategies::SIMPLE_STRATEGIES;
 mod strategies;
 
 /// Implements the `proptest` cli subcommand.
-pub fn run_proptests(strategy_name: &str, count: u32, verbosity: Verbosity, edition: Edition) {
+///
+/// Generated inputs longer than `max_input_length` bytes are rejected (in proptest's sense) rather
+/// than tested.
+///
+/// If `seed` is None, chooses a seed at random. The seed is reported if a failure is found, so that
+/// the run can be repeated.
+pub fn run_proptests(
+    strategy_name: &str,
+    count: u32,
+    verbosity: Verbosity,
+    seed: Option<u64>,
+    edition: Edition,
+    max_input_length: usize,
+) {
     println!("Running property tests with strategy {strategy_name} for {count} iterations");
-    let mut runner = TestRunner::new(Config {
-        cases: count,
-        verbose: verbosity.into(),
-        failure_persistence: None,
-        ..Config::default()
-    });
+    let seed = seed.unwrap_or_else(|| RandomState::new().hash_one(0_u8));
+    let mut runner = TestRunner::new_with_rng(
+        Config {
+            cases: count,
+            verbose: verbosity.into(),
+            failure_persistence: None,
+            ..Config::default()
+        },
+        seeded_rng(seed),
+    );
     let strategy = &named_strategy(strategy_name).expect("unknown strategy");
-    let result = runner.run(strategy, |input| match check_lexing(&input, edition) {
-        ComparisonStatus::Pass => Ok(()),
-        ComparisonStatus::Fail(msg) => Err(TestCaseError::Fail(msg.into())),
-        ComparisonStatus::Unsupported(msg) => Err(TestCaseError::Reject(msg.into())),
+    lexlucid::reset_statistics();
+    lexlucid::set_statistics_recording(true);
+    let result = runner.run(strategy, |input| {
+        if input.len() > max_input_length {
+            return Err(TestCaseError::Reject("input too long".into()));
+        }
+        match check_lexing(&input, edition) {
+            ComparisonStatus::Pass => Ok(()),
+            ComparisonStatus::Fail(msg) => Err(TestCaseError::Fail(msg.into())),
+            ComparisonStatus::Unsupported(msg) => Err(TestCaseError::Reject(msg.into())),
+        }
     });
+    lexlucid::set_statistics_recording(false);
     match result {
         Ok(_) => println!("No discrepancies found"),
         Err(TestError::Fail(reason, value)) => {
@@ -39,11 +67,46 @@ pub fn run_proptests(strategy_name: &str, count: u32, verbosity: Verbosity, edit
                 escape_for_display(&value),
                 reason
             );
+            println!("(seed {seed}: rerun with --seed={seed} to reproduce)");
+            println!("As a testcase ({edition:?}, default cleaning):");
+            println!("    {},", rust_string_literal(&value));
         }
         Err(TestError::Abort(reason)) => {
             println!("Proptest aborted: {}", reason);
         }
     }
+    report_priority_violations();
+}
+
+/// Makes a random number generator for proptest whose output is determined by `seed`.
+fn seeded_rng(seed: u64) -> TestRng {
+    let mut seed_bytes = [0; 32];
+    seed_bytes[..8].copy_from_slice(&seed.to_le_bytes());
+    TestRng::from_seed(RngAlgorithm::ChaCha, &seed_bytes)
+}
+
+/// Reports how often lexlucid's pretokeniser saw each category of priority violation during the
+/// run.
+///
+/// Says explicitly if any category other than a known exception was seen.
+fn report_priority_violations() {
+    let counts = lexlucid::priority_violation_counts();
+    if counts.is_empty() {
+        return;
+    }
+    println!("Pretokenisation priority violations by category:");
+    for (category, count) in counts.iter() {
+        println!("  {count:5}  {category:?}");
+    }
+    let unexpected = counts
+        .iter()
+        .filter(|(category, _)| !category.is_known_exception())
+        .count();
+    if unexpected != 0 {
+        println!(
+            "*** {unexpected} categories of priority violation which aren't known exceptions ***"
+        );
+    }
 }
 
 /// Checks whether the lexlucid and rustc models agree for the specified input.
@@ -72,7 +135,13 @@ enum ComparisonStatus {
 
 /// Returns a list of the names of the available strategies.
 pub fn strategy_names() -> Vec<&'static str> {
-    let mut names = vec!["any-char", "mix"];
+    let mut names = vec![
+        "any-char",
+        "mix",
+        "string-literals",
+        "raw-strings",
+        "mutate",
+    ];
     names.extend(SIMPLE_STRATEGIES.iter().map(|(name, _)| name).copied());
     names
 }
@@ -91,6 +160,15 @@ fn named_strategy(name: &str) -> Option<BoxedStrategy<String>> {
     if name == "mix" {
         return Some(strategies::mix());
     }
+    if name == "string-literals" {
+        return Some(strategies::string_literals());
+    }
+    if name == "raw-strings" {
+        return Some(strategies::raw_strings());
+    }
+    if name == "mutate" {
+        return Some(strategies::mutate());
+    }
     None
 }
 
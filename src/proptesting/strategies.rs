@@ -1,10 +1,15 @@
 use proptest::{
     array::uniform3,
-    sample::select,
-    strategy::{BoxedStrategy, Strategy},
+    collection::vec,
+    prop_oneof,
+    sample::{select, Index},
+    strategy::{BoxedStrategy, Just, Strategy},
     string::string_regex,
 };
 
+use crate::char_sequences::Charseq;
+use crate::testcases;
+
 pub const DEFAULT_STRATEGY: &str = "mix";
 
 #[rustfmt::skip]
@@ -59,3 +64,230 @@ pub(crate) fn mix() -> BoxedStrategy<String> {
         .prop_flat_map(|inputs| string_regex(&inputs.join("")).unwrap())
         .boxed()
 }
+
+/// Strategy returning a single string-family literal (which may be malformed).
+///
+/// Each literal has a prefix, optional hashes, and content made of plain characters and escapes,
+/// biased towards boundary cases (eg out-of-range unicode escapes). The closing quote and hashes
+/// are usually, but not always, correct.
+///
+/// Shrinking moves towards an unprefixed literal with few plain characters.
+pub(crate) fn string_literals() -> BoxedStrategy<String> {
+    const PREFIXES: &[&str] = ["", "b", "c", "r", "br", "cr"].as_slice();
+    const BOUNDARY_ESCAPES: &[&str] = [
+        r"\n",
+        r"\\",
+        r"\",
+        r"\0",
+        r"\x00",
+        r"\x7f",
+        r"\x80",
+        r"\xff",
+        r"\x",
+        r"\x4",
+        r"\u{}",
+        r"\u{0}",
+        r"\u{7f}",
+        r"\u{ff}",
+        r"\u{d800}",
+        r"\u{10ffff}",
+        r"\u{110000}",
+        r"\u{1_0}",
+        r"\u{0000001}",
+        r"\u",
+        "\\\n",
+        "\\\n\n  ",
+        "\\\n\t\u{a0}",
+        "\r",
+        "\r\n",
+        "\0",
+    ]
+    .as_slice();
+    // Sequences which are awkward to write (or to read) as string literals
+    const SCALAR_SEQUENCES: &[&[u32]] = &[
+        &[0x0301],         // isolated combining acute accent
+        &[0x0065, 0x0301], // e followed by combining acute accent
+        &[0x0301, 0x0301], // stacked combining marks
+        &[0x200D],         // zero width joiner
+        &[0xFEFF],         // byte order mark
+        &[0x2028],         // line separator
+    ];
+    const SUFFIXES: &[&str] = ["", "suffix", "_", "_x", "e5"].as_slice();
+
+    let piece = prop_oneof![
+        3 => string_regex(r#"[a "'#é\n]{1,3}"#).unwrap(),
+        2 => select(BOUNDARY_ESCAPES).prop_map(str::to_owned),
+        1 => string_regex(r"\\x[0-9a-fA-FgG]{0,2}").unwrap(),
+        1 => string_regex(r"\\u\{[0-9a-fA-F_]{0,7}\}").unwrap(),
+        1 => select(SCALAR_SEQUENCES).prop_map(|values| {
+            Charseq::from_scalar_values(values)
+                .expect("invalid scalar value in SCALAR_SEQUENCES")
+                .to_string()
+        }),
+    ];
+    let closing_hashes_adjustment = prop_oneof![8 => Just(0), 1 => Just(-1), 1 => Just(1)];
+    (
+        select(PREFIXES),
+        0..3_i32,
+        vec(piece, 0..6),
+        proptest::bool::weighted(0.1),
+        closing_hashes_adjustment,
+        select(SUFFIXES),
+    )
+        .prop_map(
+            |(prefix, hashes, pieces, unterminated, adjustment, suffix)| {
+                let hashes = if prefix.ends_with('r') { hashes } else { 0 };
+                let mut literal = Charseq::from(prefix);
+                literal.extend_from_str(&"#".repeat(hashes as usize));
+                literal.push_char('"');
+                for piece in pieces {
+                    literal.extend_from_str(&piece);
+                }
+                if !unterminated {
+                    literal.push_char('"');
+                    literal.extend_from_str(&"#".repeat((hashes + adjustment).max(0) as usize));
+                    literal.extend_from_str(suffix);
+                }
+                literal.to_string()
+            },
+        )
+        .boxed()
+}
+
+/// Strategy returning a single raw string literal (which may be malformed).
+///
+/// Each literal has an `r`, `br`, or `cr` prefix and up to six hashes. The content is biased
+/// towards a double quote followed by nearly enough hashes to close the literal early, and the
+/// closing hashes are usually, but not always, correct.
+///
+/// Shrinking moves towards fewer hashes and shorter content.
+pub(crate) fn raw_strings() -> BoxedStrategy<String> {
+    const PREFIXES: &[&str] = ["r", "br", "cr"].as_slice();
+    const SUFFIXES: &[&str] = ["", "x", "_", "#"].as_slice();
+
+    (select(PREFIXES), 0..=6_usize)
+        .prop_flat_map(|(prefix, hashes)| {
+            let near_closing = prop_oneof![
+                3 => Just(hashes.saturating_sub(1)),
+                1 => Just(hashes),
+                1 => 0..=hashes + 1,
+            ]
+            .prop_map(|n| format!("\"{}", "#".repeat(n)));
+            let piece = prop_oneof![
+                3 => string_regex(r#"[a #'\\é\r\n]{1,3}"#).unwrap(),
+                2 => near_closing,
+            ];
+            let closing_hashes = prop_oneof![
+                6 => Just(Some(hashes)),
+                1 => Just(Some(hashes.saturating_sub(1))),
+                1 => Just(Some(hashes + 1)),
+                1 => Just(None),
+            ];
+            (
+                Just(prefix),
+                Just(hashes),
+                vec(piece, 0..6),
+                closing_hashes,
+                select(SUFFIXES),
+            )
+        })
+        .prop_map(|(prefix, hashes, pieces, closing_hashes, suffix)| {
+            let mut literal = Charseq::from(prefix);
+            literal.extend_from_str(&"#".repeat(hashes));
+            literal.push_char('"');
+            for piece in pieces {
+                literal.extend_from_str(&piece);
+            }
+            // None means the literal is left unterminated
+            if let Some(closing_hashes) = closing_hashes {
+                literal.push_char('"');
+                literal.extend_from_str(&"#".repeat(closing_hashes));
+                literal.extend_from_str(suffix);
+            }
+            literal.to_string()
+        })
+        .boxed()
+}
+
+/// A small edit to apply to an input.
+#[derive(Clone, Debug)]
+enum Edit {
+    /// Insert a character before the indexed character (or at the end).
+    Insert(Index, char),
+    /// Delete the indexed character.
+    Delete(Index),
+    /// Swap the indexed character with the one after it.
+    Swap(Index),
+    /// Double the indexed `#`, or the first `#` after it.
+    DuplicateHash(Index),
+    /// Change the indexed quote mark, or the first quote mark after it, to the other kind.
+    FlipQuote(Index),
+}
+
+impl Edit {
+    fn apply(&self, chars: &mut Vec<char>) {
+        let find_from = |index: &Index, chars: &[char], wanted: &dyn Fn(char) -> bool| {
+            if chars.is_empty() {
+                return None;
+            }
+            let start = index.index(chars.len());
+            (start..chars.len()).find(|&i| wanted(chars[i]))
+        };
+        match self {
+            Edit::Insert(index, c) => {
+                let position = index.index(chars.len() + 1);
+                chars.insert(position, *c);
+            }
+            Edit::Delete(index) => {
+                if !chars.is_empty() {
+                    chars.remove(index.index(chars.len()));
+                }
+            }
+            Edit::Swap(index) => {
+                if chars.len() >= 2 {
+                    let position = index.index(chars.len() - 1);
+                    chars.swap(position, position + 1);
+                }
+            }
+            Edit::DuplicateHash(index) => {
+                if let Some(position) = find_from(index, chars, &|c| c == '#') {
+                    chars.insert(position, '#');
+                }
+            }
+            Edit::FlipQuote(index) => {
+                if let Some(position) = find_from(index, chars, &|c| c == '"' || c == '\'') {
+                    chars[position] = if chars[position] == '"' { '\'' } else { '"' };
+                }
+            }
+        }
+    }
+}
+
+/// Strategy returning testcases from the LONGLIST with a few small edits applied.
+///
+/// Shrinking removes edits, so it moves back towards the original testcase.
+pub(crate) fn mutate() -> BoxedStrategy<String> {
+    const INSERTABLE: &[char] = [
+        '"', '\'', '#', '\\', ' ', '\n', '\r', 'r', 'b', 'c', 'x', 'u', '0', '_', 'e', '.', '/',
+        '*', '!', '{', '}', '\u{301}',
+    ]
+    .as_slice();
+    let edit = prop_oneof![
+        (proptest::arbitrary::any::<Index>(), select(INSERTABLE))
+            .prop_map(|(index, c)| Edit::Insert(index, c)),
+        proptest::arbitrary::any::<Index>().prop_map(Edit::Delete),
+        proptest::arbitrary::any::<Index>().prop_map(Edit::Swap),
+        proptest::arbitrary::any::<Index>().prop_map(Edit::DuplicateHash),
+        proptest::arbitrary::any::<Index>().prop_map(Edit::FlipQuote),
+    ];
+    // Don't shrink towards a different testcase
+    (select(testcases::LONGLIST).no_shrink(), vec(edit, 0..4))
+        .prop_map(|(testcase, edits)| {
+            let mut chars: Vec<char> = testcase.chars().collect();
+            for edit in edits.iter() {
+                edit.apply(&mut chars);
+            }
+            chars.into_iter().collect()
+        })
+        .boxed()
+}
@@ -0,0 +1,132 @@
+//! Describes lexlucid's fine-grained tokens using `rustc_lexer`'s names for token kinds.
+//!
+//! This doesn't depend on `rustc_lexer`; it reproduces the `Debug` representation of its `Token`
+//! struct (as of the nightly this crate is pinned to), so that lexlucid's output can be set beside
+//! the output of `rustc_lexer::tokenize()` (which rust-analyzer also uses).
+//!
+//! Only the kinds which can describe a token lexlucid accepts are produced: literals are always
+//! `terminated`, integer literals are never `empty_int`, and so on.
+
+use crate::char_sequences::Charseq;
+use crate::lexlucid::{CommentStyle, FineToken, FineTokenData};
+use crate::serialization::numeric_base_name;
+
+/// Describes the tokens one per line, as [`format_ra_token`] does.
+pub fn format_ra_tokens(tokens: &[FineToken]) -> String {
+    let lines: Vec<String> = tokens.iter().map(format_ra_token).collect();
+    lines.join("\n")
+}
+
+/// Describes a token in the form `Token { kind: ..., len: ... }`.
+///
+/// `len` is the length of the token's extent in bytes.
+pub fn format_ra_token(token: &FineToken) -> String {
+    format!(
+        "Token {{ kind: {}, len: {} }}",
+        ra_kind(token),
+        token.extent.to_string().len()
+    )
+}
+
+/// Returns the `rustc_lexer::TokenKind` for the token, in the form of its `Debug` representation.
+fn ra_kind(token: &FineToken) -> String {
+    use FineTokenData::*;
+    let extent = token.extent.to_string();
+    let literal = |kind: String, suffix: &Charseq| {
+        let suffix_start = extent.len() - suffix.to_string().len();
+        format!("Literal {{ kind: {kind}, suffix_start: {suffix_start} }}")
+    };
+    // The hashes which follow the raw string literal's prefix (`r`, `br`, or `cr`)
+    let n_hashes = || {
+        extent
+            .chars()
+            .skip_while(|c| c.is_ascii_alphabetic())
+            .take_while(|c| *c == '#')
+            .count()
+    };
+    match &token.data {
+        Whitespace => "Whitespace".into(),
+        LineComment { style, .. } => format!("LineComment {{ doc_style: {} }}", doc_style(*style)),
+        BlockComment { style, .. } => format!(
+            "BlockComment {{ doc_style: {}, terminated: true }}",
+            doc_style(*style)
+        ),
+        Punctuation { mark } => punctuation_kind(*mark).into(),
+        Identifier { .. } => "Ident".into(),
+        RawIdentifier { .. } => "RawIdent".into(),
+        LifetimeOrLabel { .. } => "Lifetime { starts_with_number: false }".into(),
+        RawLifetimeOrLabel { .. } => "RawLifetime".into(),
+        CharacterLiteral { suffix, .. } => literal("Char { terminated: true }".into(), suffix),
+        ByteLiteral { suffix, .. } => literal("Byte { terminated: true }".into(), suffix),
+        StringLiteral { suffix, .. } => literal("Str { terminated: true }".into(), suffix),
+        RawStringLiteral { suffix, .. } => literal(
+            format!("RawStr {{ n_hashes: Some({}) }}", n_hashes()),
+            suffix,
+        ),
+        ByteStringLiteral { suffix, .. } => literal("ByteStr { terminated: true }".into(), suffix),
+        RawByteStringLiteral { suffix, .. } => literal(
+            format!("RawByteStr {{ n_hashes: Some({}) }}", n_hashes()),
+            suffix,
+        ),
+        CStringLiteral { suffix, .. } => literal("CStr { terminated: true }".into(), suffix),
+        RawCStringLiteral { suffix, .. } => literal(
+            format!("RawCStr {{ n_hashes: Some({}) }}", n_hashes()),
+            suffix,
+        ),
+        IntegerLiteral { base, suffix, .. } => literal(
+            format!(
+                "Int {{ base: {}, empty_int: false }}",
+                numeric_base_name(*base)
+            ),
+            suffix,
+        ),
+        FloatLiteral { suffix, .. } => literal(
+            "Float { base: Decimal, empty_exponent: false }".into(),
+            suffix,
+        ),
+    }
+}
+
+fn doc_style(style: CommentStyle) -> &'static str {
+    match style {
+        CommentStyle::NonDoc => "None",
+        CommentStyle::InnerDoc => "Some(Inner)",
+        CommentStyle::OuterDoc => "Some(Outer)",
+    }
+}
+
+fn punctuation_kind(mark: char) -> &'static str {
+    match mark {
+        ';' => "Semi",
+        ',' => "Comma",
+        '.' => "Dot",
+        '(' => "OpenParen",
+        ')' => "CloseParen",
+        '{' => "OpenBrace",
+        '}' => "CloseBrace",
+        '[' => "OpenBracket",
+        ']' => "CloseBracket",
+        '@' => "At",
+        '#' => "Pound",
+        '~' => "Tilde",
+        '?' => "Question",
+        ':' => "Colon",
+        '$' => "Dollar",
+        '=' => "Eq",
+        '!' => "Bang",
+        '<' => "Lt",
+        '>' => "Gt",
+        '-' => "Minus",
+        '&' => "And",
+        '|' => "Or",
+        '+' => "Plus",
+        '*' => "Star",
+        '/' => "Slash",
+        '^' => "Caret",
+        '%' => "Percent",
+        _ => "Unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,27 @@
+use crate::lexlucid::{self, Analysis};
+use crate::Edition;
+
+use super::format_ra_token;
+
+fn ra_tokens(input: &str) -> Vec<String> {
+    let Analysis::Accepts(_, tokens) = lexlucid::analyse(input, Edition::E2021) else {
+        panic!("{input} should be accepted");
+    };
+    tokens.iter().map(format_ra_token).collect()
+}
+
+#[test]
+fn raw_string_hashes_and_suffixes() {
+    assert_eq!(
+        ra_tokens(r###"r"a#"x br##"b"##"###),
+        [
+            "Token { kind: Literal { kind: RawStr { n_hashes: Some(0) }, suffix_start: 5 }, len: 6 }",
+            "Token { kind: Whitespace, len: 1 }",
+            "Token { kind: Literal { kind: RawByteStr { n_hashes: Some(2) }, suffix_start: 9 }, len: 9 }",
+        ]
+    );
+    assert_eq!(
+        ra_tokens("'é'suf"),
+        ["Token { kind: Literal { kind: Char { terminated: true }, suffix_start: 4 }, len: 7 }"]
+    );
+}
@@ -22,9 +22,10 @@ use crate::{
         RustcCommentKind, RustcDocCommentStyle, RustcIdentIsRaw, RustcLiteralData,
         RustcStringStyle, RustcToken, RustcTokenData, RustcTokenSpacing,
     },
+    utils::{format_represented_char, format_represented_string},
 };
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq)]
 pub struct RegularToken {
     pub extent: Charseq,
     pub spacing: Spacing,
@@ -33,7 +34,25 @@ pub struct RegularToken {
 
 impl std::fmt::Debug for RegularToken {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "extent: {:?} {:?}", self.extent, self.data)?;
+        write!(f, "extent: {:?} ", self.extent)?;
+        match &self.data {
+            RegularTokenData::CharacterLiteral {
+                represented_character,
+            } => write!(
+                f,
+                "CharacterLiteral {{ represented_character: {} }}",
+                format_represented_char(*represented_character)
+            )?,
+            RegularTokenData::StringLiteral {
+                represented_string,
+                style,
+            } => write!(
+                f,
+                "StringLiteral {{ represented_string: {}, style: {style:?} }}",
+                format_represented_string(&represented_string.to_string())
+            )?,
+            data => write!(f, "{data:?}")?,
+        }
         if matches!(self.spacing, Spacing::Alone) {
             write!(f, " |")?;
         }
@@ -41,7 +60,7 @@ impl std::fmt::Debug for RegularToken {
     }
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq)]
 pub enum Spacing {
     /// This token is followed by whitespace, a (non-doc) comment, or end-of-input.
     Alone,
@@ -52,7 +71,7 @@ pub enum Spacing {
 /// A regularised token's kind and attributes.
 ///
 /// We use Charseq rather than String here for the sake of its Debug representation.
-#[derive(PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub enum RegularTokenData {
     DocComment {
         comment_kind: CommentKind,
@@ -103,6 +122,68 @@ pub enum RegularTokenData {
     Other,
 }
 
+/// Broad classification of regularised tokens, used to select inputs of interest.
+#[derive(PartialEq, Eq, Copy, Clone, std::fmt::Debug)]
+pub enum TokenKind {
+    /// String-family literals (including character and byte literals)
+    Strings,
+    Identifiers,
+    /// Integer and floating-point literals
+    Numerics,
+    /// Doc-comments (non-doc-comments don't appear in regularised tokens)
+    Comments,
+    Punctuation,
+    Lifetimes,
+}
+
+impl RegularTokenData {
+    /// Returns the broad classification of this token, if it has one.
+    pub fn kind(&self) -> Option<TokenKind> {
+        use RegularTokenData::*;
+        match self {
+            DocComment { .. } => Some(TokenKind::Comments),
+            Punctuation => Some(TokenKind::Punctuation),
+            Identifier { .. } => Some(TokenKind::Identifiers),
+            LifetimeOrLabel { .. } => Some(TokenKind::Lifetimes),
+            ByteLiteral { .. }
+            | ByteStringLiteral { .. }
+            | CharacterLiteral { .. }
+            | StringLiteral { .. }
+            | CstringLiteral { .. }
+            | LiteralWithForbiddenSuffix { .. } => Some(TokenKind::Strings),
+            IntegerLiteral { .. } | FloatLiteral { .. } => Some(TokenKind::Numerics),
+            Other => None,
+        }
+    }
+}
+
+/// Reduces a sequence of regularised tokens to the string-family literals which have a represented
+/// value, for comparing only how literals are unescaped.
+///
+/// The remaining tokens have an empty extent and `Alone` spacing, so that only their kind (and
+/// style) and represented value are compared.
+pub fn literal_values(tokens: Vec<RegularToken>) -> Vec<RegularToken> {
+    use RegularTokenData::*;
+    tokens
+        .into_iter()
+        .filter(|token| {
+            matches!(
+                token.data,
+                ByteLiteral { .. }
+                    | ByteStringLiteral { .. }
+                    | CharacterLiteral { .. }
+                    | StringLiteral { .. }
+                    | CstringLiteral { .. }
+            )
+        })
+        .map(|token| RegularToken {
+            extent: Charseq::new(Vec::new()),
+            spacing: Spacing::Alone,
+            data: token.data,
+        })
+        .collect()
+}
+
 /// Line or block comment
 #[derive(PartialEq, Eq, Copy, Clone, std::fmt::Debug)]
 pub enum CommentKind {
@@ -332,6 +413,11 @@ fn from_coarse_token(token: CoarseToken) -> RegularTokenData {
             mut represented_bytes,
             ..
         } => {
+            debug_assert_ne!(
+                represented_bytes.last(),
+                Some(&0),
+                "C string's represented bytes already include the terminating NUL"
+            );
             represented_bytes.push(0);
             RegularTokenData::CstringLiteral {
                 represented_bytes,
@@ -354,6 +440,11 @@ fn from_coarse_token(token: CoarseToken) -> RegularTokenData {
             mut represented_bytes,
             ..
         } => {
+            debug_assert_ne!(
+                represented_bytes.last(),
+                Some(&0),
+                "C string's represented bytes already include the terminating NUL"
+            );
             represented_bytes.push(0);
             RegularTokenData::CstringLiteral {
                 represented_bytes,
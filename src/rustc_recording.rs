@@ -0,0 +1,423 @@
+//! Recording rustc's regularised output, so that lexlucid can be compared with it later without
+//! running rustc.
+//!
+//! A recording is a text file. Its first line is `lexeywan-rustc-recording` followed by the
+//! edition. Each input is then described by an `input` line holding the input, followed by one of:
+//!  - an `accepts` line, then a `token` line for each regularised token
+//!  - a `rejects` line, then a `message` line for each of rustc's error messages
+//!  - a `model-error` line, then a `message` line for each message
+//!
+//! Strings are written in double quotes, escaped as in JSON. Byte sequences are written as
+//! strings of hexadecimal digits.
+//!
+//! The category of a recorded rejection isn't stored; it's worked out from the messages when the
+//! recording is read.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::char_sequences::Charseq;
+use crate::comparison::{categorise_rustc_rejection, regularised_from_rustc, Regularisation};
+use crate::regular_tokens::{
+    CommentKind, DocCommentStyle, IdentifierStyle, RegularToken, RegularTokenData, Spacing,
+    StringStyle,
+};
+use crate::serialization::json_string;
+use crate::Edition;
+
+const HEADER: &str = "lexeywan-rustc-recording";
+
+/// rustc's regularised output for a set of inputs, read from a recording.
+pub struct RustcRecording {
+    results: HashMap<String, Regularisation>,
+}
+
+impl RustcRecording {
+    /// Returns rustc's recorded output for the input.
+    ///
+    /// Reports a model error if the input isn't in the recording.
+    pub fn regularisation(&self, input: &str) -> Regularisation {
+        match self.results.get(input) {
+            Some(regularisation) => regularisation.clone(),
+            None => Regularisation::ModelError(vec!["input isn't in the rustc recording".into()]),
+        }
+    }
+}
+
+/// Implements the `record-rustc` CLI command.
+///
+/// Lexes each input with rustc, and writes the regularised results to `path`.
+///
+/// Returns the exit status: 0 if the recording was written, and 4 if it couldn't be.
+pub fn run_record_rustc_subcommand(inputs: &[&str], edition: Edition, path: &Path) -> u8 {
+    let mut lines = vec![format!("{HEADER} {edition:?}")];
+    for input in inputs {
+        lines.push(format!("input {}", json_string(input)));
+        let messages = match regularised_from_rustc(input, edition) {
+            Regularisation::Accepts(tokens) => {
+                lines.push("accepts".into());
+                lines.extend(tokens.iter().map(token_line));
+                continue;
+            }
+            Regularisation::Rejects(_, messages) => {
+                lines.push("rejects".into());
+                messages
+            }
+            Regularisation::ModelError(messages) => {
+                lines.push("model-error".into());
+                messages
+            }
+        };
+        lines.extend(
+            messages
+                .iter()
+                .map(|s| format!("message {}", json_string(s))),
+        );
+    }
+    lines.push(String::new());
+    if let Err(e) = std::fs::write(path, lines.join("\n")) {
+        eprintln!("can't write {}: {e}", path.display());
+        return 4;
+    }
+    println!(
+        "recorded rustc's output for {} inputs in {}",
+        inputs.len(),
+        path.display()
+    );
+    0
+}
+
+/// Reads a recording made by `record-rustc`.
+///
+/// Reports an error if the recording can't be read or parsed, or if it was made for an edition
+/// other than `edition`.
+pub fn read_recording(path: &Path, edition: Edition) -> Result<RustcRecording, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("can't read recording: {e}"))?;
+    let mut lines = text.lines().enumerate();
+    let recorded_edition = match lines.next().map(|(_, line)| line.split_once(' ')) {
+        Some(Some((HEADER, recorded_edition))) => recorded_edition,
+        _ => return Err("not a rustc recording".into()),
+    };
+    if recorded_edition != format!("{edition:?}") {
+        return Err(format!(
+            "the recording is for edition {recorded_edition}, not {edition:?}"
+        ));
+    }
+    let mut results = HashMap::new();
+    let mut current: Option<(String, Regularisation)> = None;
+    for (index, line) in lines {
+        let fields = split_fields(line).map_err(|e| format!("line {}: {e}", index + 1))?;
+        parse_line(&fields, &mut current, &mut results)
+            .map_err(|e| format!("line {}: {e}", index + 1))?;
+    }
+    results.extend(current);
+    Ok(RustcRecording { results })
+}
+
+/// Interprets a line of a recording (other than the header).
+///
+/// `current` holds the input described by the preceding lines, and its result so far; it's moved
+/// to `results` when the next input begins.
+fn parse_line(
+    fields: &[String],
+    current: &mut Option<(String, Regularisation)>,
+    results: &mut HashMap<String, Regularisation>,
+) -> Result<(), String> {
+    let Some((keyword, fields)) = fields.split_first() else {
+        return Ok(());
+    };
+    if keyword == "input" {
+        let [input] = fields else {
+            return Err("expected a single input".into());
+        };
+        results.extend(current.replace((input.clone(), Regularisation::ModelError(Vec::new()))));
+        return Ok(());
+    }
+    let Some((input, regularisation)) = current else {
+        return Err(format!("'{keyword}' before any input"));
+    };
+    match (keyword.as_str(), regularisation) {
+        ("accepts", result) => *result = Regularisation::Accepts(Vec::new()),
+        ("rejects", result) => *result = Regularisation::Rejects(None, Vec::new()),
+        ("model-error", result) => *result = Regularisation::ModelError(Vec::new()),
+        ("token", Regularisation::Accepts(tokens)) => tokens.push(parse_token(fields)?),
+        ("message", Regularisation::Rejects(category, messages)) => {
+            messages.push(single_field(fields)?.clone());
+            *category = categorise_rustc_rejection(messages);
+        }
+        ("message", Regularisation::ModelError(messages)) => {
+            messages.push(single_field(fields)?.clone())
+        }
+        _ => {
+            return Err(format!(
+                "unexpected '{keyword}' for input «{}»",
+                crate::utils::escape_for_display(input)
+            ))
+        }
+    }
+    Ok(())
+}
+
+fn single_field(fields: &[String]) -> Result<&String, String> {
+    match fields {
+        [field] => Ok(field),
+        _ => Err("expected a single field".into()),
+    }
+}
+
+/// Describes a regularised token as a `token` line.
+fn token_line(token: &RegularToken) -> String {
+    use RegularTokenData::*;
+    let spacing = match token.spacing {
+        Spacing::Alone => "alone",
+        Spacing::Joint => "joint",
+    };
+    let data = match &token.data {
+        DocComment {
+            comment_kind,
+            style,
+            body,
+        } => format!(
+            "DocComment {} {} {}",
+            match comment_kind {
+                CommentKind::Line => "line",
+                CommentKind::Block => "block",
+            },
+            match style {
+                DocCommentStyle::Inner => "inner",
+                DocCommentStyle::Outer => "outer",
+            },
+            quote_charseq(body)
+        ),
+        Punctuation => "Punctuation".into(),
+        Identifier {
+            represented_identifier,
+            style,
+        } => format!(
+            "Identifier {} {}",
+            identifier_style_name(*style),
+            quote_charseq(represented_identifier)
+        ),
+        LifetimeOrLabel { symbol, style } => format!(
+            "LifetimeOrLabel {} {}",
+            identifier_style_name(*style),
+            quote_charseq(symbol)
+        ),
+        ByteLiteral { represented_byte } => format!("ByteLiteral {represented_byte}"),
+        ByteStringLiteral {
+            represented_bytes,
+            style,
+        } => format!(
+            "ByteStringLiteral {} {}",
+            string_style_name(*style),
+            quote_bytes(represented_bytes)
+        ),
+        CharacterLiteral {
+            represented_character,
+        } => format!(
+            "CharacterLiteral {}",
+            json_string(represented_character.encode_utf8(&mut [0; 4]))
+        ),
+        StringLiteral {
+            represented_string,
+            style,
+        } => format!(
+            "StringLiteral {} {}",
+            string_style_name(*style),
+            quote_charseq(represented_string)
+        ),
+        CstringLiteral {
+            represented_bytes,
+            style,
+        } => format!(
+            "CstringLiteral {} {}",
+            string_style_name(*style),
+            quote_bytes(represented_bytes)
+        ),
+        IntegerLiteral { suffix } => format!("IntegerLiteral {}", quote_charseq(suffix)),
+        FloatLiteral { suffix } => format!("FloatLiteral {}", quote_charseq(suffix)),
+        LiteralWithForbiddenSuffix { suffix } => {
+            format!("LiteralWithForbiddenSuffix {}", quote_charseq(suffix))
+        }
+        Other => "Other".into(),
+    };
+    format!("token {spacing} {} {data}", quote_charseq(&token.extent))
+}
+
+/// Interprets the fields of a `token` line.
+fn parse_token(fields: &[String]) -> Result<RegularToken, String> {
+    use RegularTokenData::*;
+    let [spacing, extent, kind, fields @ ..] = fields else {
+        return Err("incomplete token".into());
+    };
+    let spacing = match spacing.as_str() {
+        "alone" => Spacing::Alone,
+        "joint" => Spacing::Joint,
+        _ => return Err(format!("unknown spacing '{spacing}'")),
+    };
+    let data = match (kind.as_str(), fields) {
+        ("DocComment", [comment_kind, style, body]) => DocComment {
+            comment_kind: match comment_kind.as_str() {
+                "line" => CommentKind::Line,
+                "block" => CommentKind::Block,
+                _ => return Err(format!("unknown comment kind '{comment_kind}'")),
+            },
+            style: match style.as_str() {
+                "inner" => DocCommentStyle::Inner,
+                "outer" => DocCommentStyle::Outer,
+                _ => return Err(format!("unknown doc-comment style '{style}'")),
+            },
+            body: body.as_str().into(),
+        },
+        ("Punctuation", []) => Punctuation,
+        ("Identifier", [style, represented_identifier]) => Identifier {
+            represented_identifier: represented_identifier.as_str().into(),
+            style: parse_identifier_style(style)?,
+        },
+        ("LifetimeOrLabel", [style, symbol]) => LifetimeOrLabel {
+            symbol: symbol.as_str().into(),
+            style: parse_identifier_style(style)?,
+        },
+        ("ByteLiteral", [represented_byte]) => ByteLiteral {
+            represented_byte: represented_byte
+                .parse()
+                .map_err(|_| format!("bad byte '{represented_byte}'"))?,
+        },
+        ("ByteStringLiteral", [style, represented_bytes]) => ByteStringLiteral {
+            represented_bytes: parse_bytes(represented_bytes)?,
+            style: parse_string_style(style)?,
+        },
+        ("CharacterLiteral", [represented_character]) => {
+            let mut chars = represented_character.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => CharacterLiteral {
+                    represented_character: c,
+                },
+                _ => return Err("a character literal must represent one character".into()),
+            }
+        }
+        ("StringLiteral", [style, represented_string]) => StringLiteral {
+            represented_string: represented_string.as_str().into(),
+            style: parse_string_style(style)?,
+        },
+        ("CstringLiteral", [style, represented_bytes]) => CstringLiteral {
+            represented_bytes: parse_bytes(represented_bytes)?,
+            style: parse_string_style(style)?,
+        },
+        ("IntegerLiteral", [suffix]) => IntegerLiteral {
+            suffix: suffix.as_str().into(),
+        },
+        ("FloatLiteral", [suffix]) => FloatLiteral {
+            suffix: suffix.as_str().into(),
+        },
+        ("LiteralWithForbiddenSuffix", [suffix]) => LiteralWithForbiddenSuffix {
+            suffix: suffix.as_str().into(),
+        },
+        ("Other", []) => Other,
+        _ => return Err(format!("bad '{kind}' token")),
+    };
+    Ok(RegularToken {
+        extent: extent.as_str().into(),
+        spacing,
+        data,
+    })
+}
+
+fn identifier_style_name(style: IdentifierStyle) -> &'static str {
+    match style {
+        IdentifierStyle::NonRaw => "nonraw",
+        IdentifierStyle::Raw => "raw",
+    }
+}
+
+fn parse_identifier_style(name: &str) -> Result<IdentifierStyle, String> {
+    match name {
+        "nonraw" => Ok(IdentifierStyle::NonRaw),
+        "raw" => Ok(IdentifierStyle::Raw),
+        _ => Err(format!("unknown identifier style '{name}'")),
+    }
+}
+
+fn string_style_name(style: StringStyle) -> &'static str {
+    match style {
+        StringStyle::NonRaw => "nonraw",
+        StringStyle::Raw => "raw",
+    }
+}
+
+fn parse_string_style(name: &str) -> Result<StringStyle, String> {
+    match name {
+        "nonraw" => Ok(StringStyle::NonRaw),
+        "raw" => Ok(StringStyle::Raw),
+        _ => Err(format!("unknown string style '{name}'")),
+    }
+}
+
+fn quote_charseq(chars: &Charseq) -> String {
+    json_string(&chars.to_string())
+}
+
+fn quote_bytes(bytes: &[u8]) -> String {
+    json_string(&bytes.iter().map(|b| format!("{b:02x}")).collect::<String>())
+}
+
+// is_multiple_of() isn't stable on the pinned toolchain
+#[allow(unknown_lints, clippy::manual_is_multiple_of)]
+fn parse_bytes(hex: &str) -> Result<Vec<u8>, String> {
+    if hex.len() % 2 != 0 || !hex.is_ascii() {
+        return Err(format!("bad byte sequence '{hex}'"));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| format!("bad byte sequence '{hex}'"))
+        })
+        .collect()
+}
+
+/// Splits a line into space-separated fields, removing the quotes and escapes from quoted fields.
+fn split_fields(line: &str) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        while chars.next_if_eq(&' ').is_some() {}
+        let Some(c) = chars.next() else {
+            return Ok(fields);
+        };
+        let mut field = String::new();
+        if c != '"' {
+            field.push(c);
+            while let Some(c) = chars.next_if(|c| *c != ' ') {
+                field.push(c);
+            }
+            fields.push(field);
+            continue;
+        }
+        loop {
+            match chars.next() {
+                None => return Err("unterminated string".into()),
+                Some('"') => break,
+                Some('\\') => field.push(match chars.next() {
+                    Some('"') => '"',
+                    Some('\\') => '\\',
+                    Some('n') => '\n',
+                    Some('r') => '\r',
+                    Some('t') => '\t',
+                    Some('u') => {
+                        let hex: String = chars.by_ref().take(4).collect();
+                        u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| format!("bad escape '\\u{hex}'"))?
+                    }
+                    _ => return Err("bad escape".into()),
+                }),
+                Some(c) => field.push(c),
+            }
+        }
+        fields.push(field);
+    }
+}
+
+#[cfg(test)]
+mod tests;
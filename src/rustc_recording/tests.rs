@@ -0,0 +1,64 @@
+use crate::regular_tokens::{
+    CommentKind, DocCommentStyle, IdentifierStyle, RegularToken, RegularTokenData, Spacing,
+    StringStyle,
+};
+
+use super::{parse_token, split_fields, token_line};
+
+#[test]
+fn token_lines_roundtrip() {
+    use RegularTokenData::*;
+    let tokens = [
+        DocComment {
+            comment_kind: CommentKind::Block,
+            style: DocCommentStyle::Inner,
+            body: " a \"b\"\n\tc\\ ".into(),
+        },
+        Punctuation,
+        Identifier {
+            represented_identifier: "é".into(),
+            style: IdentifierStyle::Raw,
+        },
+        LifetimeOrLabel {
+            symbol: "'a".into(),
+            style: IdentifierStyle::NonRaw,
+        },
+        ByteLiteral {
+            represented_byte: 255,
+        },
+        ByteStringLiteral {
+            represented_bytes: vec![0, 0x7f, 0xab],
+            style: StringStyle::Raw,
+        },
+        CharacterLiteral {
+            represented_character: '\u{1}',
+        },
+        StringLiteral {
+            represented_string: "".into(),
+            style: StringStyle::NonRaw,
+        },
+        CstringLiteral {
+            represented_bytes: vec![],
+            style: StringStyle::NonRaw,
+        },
+        IntegerLiteral {
+            suffix: "u8".into(),
+        },
+        FloatLiteral { suffix: "".into() },
+        LiteralWithForbiddenSuffix {
+            suffix: "x y".into(),
+        },
+        Other,
+    ];
+    for data in tokens {
+        let token = RegularToken {
+            extent: "🦀 \"".into(),
+            spacing: Spacing::Joint,
+            data,
+        };
+        let line = token_line(&token);
+        let fields = split_fields(&line).unwrap();
+        assert_eq!(fields[0], "token");
+        assert_eq!(parse_token(&fields[1..]), Ok(token), "{line}");
+    }
+}
@@ -0,0 +1,307 @@
+//! Serialisation of lexlucid's output as JSON.
+//!
+//! The output is intended to be consumed by other tools (for example `jq`).
+//!
+//! Each token is represented as an object with a `kind` member naming its `FineTokenData` variant,
+//! an `extent` member, `start` and `end` members giving its span (as byte offsets into the cleaned
+//! input), and a member for each of the variant's fields.
+//!
+//! `Charseq`s and `char`s are represented as JSON strings, and sequences of bytes are represented
+//! as arrays of integers. The values of numeric literals are represented as JSON strings (so that
+//! large integers and infinities survive), with `null` for an integer too large for a `u128`.
+
+use crate::char_sequences::Charseq;
+use crate::lexlucid::{
+    self, CommentStyle, FineToken, FineTokenData, FloatValue, NumericBase, StringContinuation,
+};
+
+/// Represents an input as a single-line JSON string.
+pub fn input_as_json(input: &str) -> String {
+    json_string(input)
+}
+
+/// Describes the result of lexlucid's analysis of a single input as a single-line JSON object.
+///
+/// The object has an `input` member, and a `verdict` member which is one of "accepted",
+/// "rejected", or "model error".
+///
+/// If the input was accepted, there is a `tokens` member holding an array of fine-grained tokens.
+/// Otherwise there is a `messages` member holding an array of strings. If the input was rejected
+/// there is also a `category` member naming the `RejectionCategory`.
+pub fn analysis_as_json(input: &str, analysis: lexlucid::Analysis) -> String {
+    let mut object = JsonObject::new();
+    object.add("input", json_string(input));
+    match analysis {
+        lexlucid::Analysis::Accepts(_, tokens) => {
+            object.add("verdict", json_string("accepted"));
+            object.add("tokens", json_array(tokens.iter().map(token_as_json)));
+        }
+        lexlucid::Analysis::Rejects(category, reason) => {
+            object.add("verdict", json_string("rejected"));
+            object.add("category", json_string(&format!("{category:?}")));
+            object.add("messages", json_messages(reason.into_description()));
+        }
+        lexlucid::Analysis::ModelError(reason) => {
+            object.add("verdict", json_string("model error"));
+            object.add("messages", json_messages(reason.into_description()));
+        }
+    }
+    object.finish()
+}
+
+/// Describes lexlucid's rejection of an input before lexical analysis (during cleaning) as a
+/// single-line JSON object.
+///
+/// The object has the same form as [`analysis_as_json`]'s, with verdict "rejected".
+pub fn cleaning_rejection_as_json(input: &str, message: &str) -> String {
+    let mut object = JsonObject::new();
+    object.add("input", json_string(input));
+    object.add("verdict", json_string("rejected"));
+    object.add("messages", json_messages(vec![message.to_owned()]));
+    object.finish()
+}
+
+/// Describes a fine-grained token as a JSON object.
+pub fn token_as_json(token: &FineToken) -> String {
+    use FineTokenData::*;
+    let mut object = JsonObject::new();
+    let kind = match &token.data {
+        Whitespace => "Whitespace",
+        LineComment { .. } => "LineComment",
+        BlockComment { .. } => "BlockComment",
+        Punctuation { .. } => "Punctuation",
+        Identifier { .. } => "Identifier",
+        RawIdentifier { .. } => "RawIdentifier",
+        LifetimeOrLabel { .. } => "LifetimeOrLabel",
+        RawLifetimeOrLabel { .. } => "RawLifetimeOrLabel",
+        CharacterLiteral { .. } => "CharacterLiteral",
+        ByteLiteral { .. } => "ByteLiteral",
+        StringLiteral { .. } => "StringLiteral",
+        RawStringLiteral { .. } => "RawStringLiteral",
+        ByteStringLiteral { .. } => "ByteStringLiteral",
+        RawByteStringLiteral { .. } => "RawByteStringLiteral",
+        CStringLiteral { .. } => "CStringLiteral",
+        RawCStringLiteral { .. } => "RawCStringLiteral",
+        IntegerLiteral { .. } => "IntegerLiteral",
+        FloatLiteral { .. } => "FloatLiteral",
+    };
+    object.add("kind", json_string(kind));
+    object.add("extent", json_charseq(&token.extent));
+    object.add("start", token.span.start.to_string());
+    object.add("end", token.span.end.to_string());
+    match &token.data {
+        Whitespace => {}
+        LineComment { style, body } | BlockComment { style, body } => {
+            object.add("style", json_string(comment_style_name(*style)));
+            object.add("body", json_charseq(body));
+        }
+        Punctuation { mark } => {
+            object.add("mark", json_char(*mark));
+        }
+        Identifier {
+            represented_identifier,
+            original_identifier,
+        }
+        | RawIdentifier {
+            represented_identifier,
+            original_identifier,
+        } => {
+            object.add(
+                "represented_identifier",
+                json_charseq(represented_identifier),
+            );
+            if let Some(original_identifier) = original_identifier {
+                object.add("original_identifier", json_charseq(original_identifier));
+            }
+        }
+        LifetimeOrLabel { name } | RawLifetimeOrLabel { name } => {
+            object.add("name", json_charseq(name));
+        }
+        CharacterLiteral {
+            represented_character,
+            suffix,
+        } => {
+            object.add("represented_character", json_char(*represented_character));
+            object.add("suffix", json_charseq(suffix));
+        }
+        ByteLiteral {
+            represented_byte,
+            suffix,
+        } => {
+            object.add("represented_byte", represented_byte.to_string());
+            object.add("suffix", json_charseq(suffix));
+        }
+        StringLiteral {
+            represented_string,
+            suffix,
+            continuations,
+        } => {
+            object.add("represented_string", json_charseq(represented_string));
+            object.add("suffix", json_charseq(suffix));
+            object.add(
+                "continuations",
+                json_array(continuations.iter().map(continuation_as_json)),
+            );
+        }
+        RawStringLiteral {
+            represented_string,
+            suffix,
+        } => {
+            object.add("represented_string", json_charseq(represented_string));
+            object.add("suffix", json_charseq(suffix));
+        }
+        ByteStringLiteral {
+            represented_bytes,
+            suffix,
+            continuations,
+        }
+        | CStringLiteral {
+            represented_bytes,
+            suffix,
+            continuations,
+        } => {
+            object.add("represented_bytes", json_bytes(represented_bytes));
+            object.add("suffix", json_charseq(suffix));
+            object.add(
+                "continuations",
+                json_array(continuations.iter().map(continuation_as_json)),
+            );
+        }
+        RawByteStringLiteral {
+            represented_bytes,
+            suffix,
+        }
+        | RawCStringLiteral {
+            represented_bytes,
+            suffix,
+        } => {
+            object.add("represented_bytes", json_bytes(represented_bytes));
+            object.add("suffix", json_charseq(suffix));
+        }
+        IntegerLiteral {
+            base,
+            digits,
+            suffix,
+            represented_value,
+        } => {
+            object.add("base", json_string(numeric_base_name(*base)));
+            object.add("digits", json_charseq(digits));
+            object.add("suffix", json_charseq(suffix));
+            object.add(
+                "represented_value",
+                match represented_value {
+                    Some(value) => json_string(&value.to_string()),
+                    None => "null".into(),
+                },
+            );
+        }
+        FloatLiteral {
+            body,
+            suffix,
+            represented_value,
+        } => {
+            object.add("body", json_charseq(body));
+            object.add("suffix", json_charseq(suffix));
+            object.add(
+                "represented_value",
+                json_string(&match represented_value {
+                    FloatValue::F32(value) => value.to_string(),
+                    FloatValue::F64(value) => value.to_string(),
+                }),
+            );
+        }
+    }
+    object.finish()
+}
+
+fn continuation_as_json(continuation: &StringContinuation) -> String {
+    let mut object = JsonObject::new();
+    object.add("start", continuation.skipped.start.to_string());
+    object.add("end", continuation.skipped.end.to_string());
+    object.add(
+        "skips_multiple_lines",
+        continuation.skips_multiple_lines.to_string(),
+    );
+    object.finish()
+}
+
+fn comment_style_name(style: CommentStyle) -> &'static str {
+    match style {
+        CommentStyle::NonDoc => "NonDoc",
+        CommentStyle::InnerDoc => "InnerDoc",
+        CommentStyle::OuterDoc => "OuterDoc",
+    }
+}
+
+/// Returns the name of a numeric base, as used in the JSON output and rustc_lexer's `Base`.
+pub(crate) fn numeric_base_name(base: NumericBase) -> &'static str {
+    match base {
+        NumericBase::Binary => "Binary",
+        NumericBase::Octal => "Octal",
+        NumericBase::Decimal => "Decimal",
+        NumericBase::Hexadecimal => "Hexadecimal",
+    }
+}
+
+/// Accumulates the members of a JSON object.
+///
+/// Values must already be serialised.
+struct JsonObject {
+    members: Vec<String>,
+}
+
+impl JsonObject {
+    fn new() -> Self {
+        JsonObject {
+            members: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, name: &str, value: String) {
+        self.members
+            .push(format!("{}:{}", json_string(name), value));
+    }
+
+    fn finish(self) -> String {
+        format!("{{{}}}", self.members.join(","))
+    }
+}
+
+fn json_array(values: impl Iterator<Item = String>) -> String {
+    format!("[{}]", values.collect::<Vec<_>>().join(","))
+}
+
+fn json_messages(messages: Vec<String>) -> String {
+    json_array(messages.iter().map(|message| json_string(message)))
+}
+
+fn json_bytes(bytes: &[u8]) -> String {
+    json_array(bytes.iter().map(|b| b.to_string()))
+}
+
+fn json_char(c: char) -> String {
+    json_string(c.encode_utf8(&mut [0; 4]))
+}
+
+fn json_charseq(chars: &Charseq) -> String {
+    json_string(&chars.to_string())
+}
+
+/// Represents a string as a JSON string literal.
+pub(crate) fn json_string(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
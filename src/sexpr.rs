@@ -0,0 +1,245 @@
+//! Describes lexlucid's fine-grained tokens as S-expressions.
+//!
+//! Tokens between matching delimiters are nested in a group, written as `(paren ...)`,
+//! `(bracket ...)`, or `(brace ...)`, so the output has the shape of rustc's token trees. A
+//! delimiter which has no match is written as an ordinary punctuation token.
+//!
+//! Whitespace and non-doc comments are omitted.
+//!
+//! Strings are written with the escapes from R7RS Scheme (`\"`, `\\`, and `\xHH;` for control
+//! characters), and byte sequences are written as R7RS bytevectors (`#u8(...)`).
+
+use crate::char_sequences::Charseq;
+use crate::lexlucid::{CommentStyle, FineToken, FineTokenData, NumericBase};
+
+/// Describes the tokens as a single S-expression of the form `(tokens ...)`.
+pub fn format_sexpr(tokens: &[FineToken]) -> String {
+    let tokens: Vec<&FineToken> = tokens
+        .iter()
+        .filter(|token| is_significant(token))
+        .collect();
+    let partners = match_delimiters(&tokens);
+    let mut out = String::from("(tokens");
+    let mut index = 0;
+    while index < tokens.len() {
+        out.push(' ');
+        index = write_tree(&tokens, &partners, index, &mut out);
+    }
+    out.push(')');
+    out
+}
+
+/// Says whether the token appears in the output (that is, it isn't whitespace or a non-doc
+/// comment).
+fn is_significant(token: &FineToken) -> bool {
+    !matches!(
+        token.data,
+        FineTokenData::Whitespace
+            | FineTokenData::LineComment {
+                style: CommentStyle::NonDoc,
+                ..
+            }
+            | FineTokenData::BlockComment {
+                style: CommentStyle::NonDoc,
+                ..
+            }
+    )
+}
+
+/// Returns the group name for an opening delimiter and the closing delimiter which matches it.
+fn group_kind(mark: char) -> Option<(&'static str, char)> {
+    match mark {
+        '(' => Some(("paren", ')')),
+        '[' => Some(("bracket", ']')),
+        '{' => Some(("brace", '}')),
+        _ => None,
+    }
+}
+
+fn punctuation_mark(token: &FineToken) -> Option<char> {
+    match token.data {
+        FineTokenData::Punctuation { mark } => Some(mark),
+        _ => None,
+    }
+}
+
+/// For each token which is an opening delimiter with a matching closing delimiter, returns the
+/// index of the closing delimiter.
+///
+/// A closing delimiter which doesn't match the innermost open group is left unmatched (and so is
+/// that group, unless a later delimiter closes it).
+fn match_delimiters(tokens: &[&FineToken]) -> Vec<Option<usize>> {
+    let mut partners = vec![None; tokens.len()];
+    let mut open: Vec<(usize, char)> = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        let Some(mark) = punctuation_mark(token) else {
+            continue;
+        };
+        if let Some((_, closer)) = group_kind(mark) {
+            open.push((index, closer));
+        } else if open.last().is_some_and(|(_, closer)| *closer == mark) {
+            let (opener, _) = open.pop().unwrap();
+            partners[opener] = Some(index);
+        }
+    }
+    partners
+}
+
+/// Writes the token at `index` (or the group it opens), returning the index of the next token.
+fn write_tree(
+    tokens: &[&FineToken],
+    partners: &[Option<usize>],
+    index: usize,
+    out: &mut String,
+) -> usize {
+    let Some(close) = partners[index] else {
+        out.push_str(&format_token(tokens[index]));
+        return index + 1;
+    };
+    let (name, _) = group_kind(punctuation_mark(tokens[index]).unwrap()).unwrap();
+    out.push('(');
+    out.push_str(name);
+    let mut inner = index + 1;
+    while inner < close {
+        out.push(' ');
+        inner = write_tree(tokens, partners, inner, out);
+    }
+    out.push(')');
+    close + 1
+}
+
+/// Describes a single token, in the form `(kind value)` or `(kind value :suffix "...")`.
+fn format_token(token: &FineToken) -> String {
+    use FineTokenData::*;
+    let with_suffix = |kind: &str, value: String, suffix: &Charseq| {
+        if suffix.is_empty() {
+            format!("({kind} {value})")
+        } else {
+            format!(
+                "({kind} {value} :suffix {})",
+                sexpr_string(&suffix.to_string())
+            )
+        }
+    };
+    match &token.data {
+        Whitespace => "(whitespace)".into(),
+        LineComment { style, body } | BlockComment { style, body } => format!(
+            "(doc-comment {} {})",
+            doc_style(*style),
+            sexpr_string(&body.to_string())
+        ),
+        Punctuation { mark } => format!("(punct {})", sexpr_string(&mark.to_string())),
+        Identifier {
+            represented_identifier,
+            ..
+        } => format!(
+            "(ident {})",
+            sexpr_string(&represented_identifier.to_string())
+        ),
+        RawIdentifier {
+            represented_identifier,
+            ..
+        } => format!(
+            "(raw-ident {})",
+            sexpr_string(&represented_identifier.to_string())
+        ),
+        LifetimeOrLabel { name } => format!("(lifetime {})", sexpr_string(&name.to_string())),
+        RawLifetimeOrLabel { name } => {
+            format!("(raw-lifetime {})", sexpr_string(&name.to_string()))
+        }
+        CharacterLiteral {
+            represented_character,
+            suffix,
+        } => with_suffix(
+            "char",
+            sexpr_string(&represented_character.to_string()),
+            suffix,
+        ),
+        ByteLiteral {
+            represented_byte,
+            suffix,
+        } => with_suffix("byte", represented_byte.to_string(), suffix),
+        StringLiteral {
+            represented_string,
+            suffix,
+            ..
+        } => with_suffix(
+            "string",
+            sexpr_string(&represented_string.to_string()),
+            suffix,
+        ),
+        RawStringLiteral {
+            represented_string,
+            suffix,
+        } => with_suffix(
+            "raw-string",
+            sexpr_string(&represented_string.to_string()),
+            suffix,
+        ),
+        ByteStringLiteral {
+            represented_bytes,
+            suffix,
+            ..
+        } => with_suffix("byte-string", bytevector(represented_bytes), suffix),
+        RawByteStringLiteral {
+            represented_bytes,
+            suffix,
+        } => with_suffix("raw-byte-string", bytevector(represented_bytes), suffix),
+        CStringLiteral {
+            represented_bytes,
+            suffix,
+            ..
+        } => with_suffix("c-string", bytevector(represented_bytes), suffix),
+        RawCStringLiteral {
+            represented_bytes,
+            suffix,
+        } => with_suffix("raw-c-string", bytevector(represented_bytes), suffix),
+        IntegerLiteral {
+            base,
+            digits,
+            suffix,
+            ..
+        } => {
+            let digits = sexpr_string(&digits.to_string());
+            match base {
+                NumericBase::Decimal => with_suffix("int", digits, suffix),
+                _ => with_suffix("int", format!("{digits} :base {}", base.radix()), suffix),
+            }
+        }
+        FloatLiteral { body, suffix, .. } => {
+            with_suffix("float", sexpr_string(&body.to_string()), suffix)
+        }
+    }
+}
+
+fn doc_style(style: CommentStyle) -> &'static str {
+    match style {
+        CommentStyle::NonDoc => "non-doc",
+        CommentStyle::InnerDoc => "inner",
+        CommentStyle::OuterDoc => "outer",
+    }
+}
+
+/// Writes a string literal, escaping `"`, `\`, and control characters.
+fn sexpr_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => out.push_str(&format!("\\x{:02x};", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Writes a bytevector literal.
+fn bytevector(bytes: &[u8]) -> String {
+    let bytes: Vec<String> = bytes.iter().map(u8::to_string).collect();
+    format!("#u8({})", bytes.join(" "))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,37 @@
+use crate::lexlucid::{self, Analysis};
+use crate::Edition;
+
+use super::format_sexpr;
+
+fn sexpr(input: &str) -> String {
+    let Analysis::Accepts(_, tokens) = lexlucid::analyse(input, Edition::E2021) else {
+        panic!("{input} should be accepted");
+    };
+    format_sexpr(&tokens)
+}
+
+#[test]
+fn groups_are_nested() {
+    assert_eq!(
+        sexpr("f(x, [1u8]) { /* c */ }"),
+        r#"(tokens (ident "f") (paren (ident "x") (punct ",") (bracket (int "1" :suffix "u8"))) (brace))"#
+    );
+}
+
+#[test]
+fn unmatched_delimiters_are_punctuation() {
+    assert_eq!(sexpr("( ] )"), r#"(tokens (paren (punct "]")))"#);
+    assert_eq!(sexpr("{ ("), r#"(tokens (punct "{") (punct "("))"#);
+}
+
+#[test]
+fn literal_values_are_escaped() {
+    assert_eq!(
+        sexpr(r#"'"' "a\"b\\c\n" b"hi" cr"x" 0x2a 1.5e3f32 /// doc"#),
+        concat!(
+            r#"(tokens (char "\"") (string "a\"b\\c\x0a;") (byte-string #u8(104 105))"#,
+            r#" (raw-c-string #u8(120)) (int "2a" :base 16) (float "1.5e3" :suffix "f32")"#,
+            r#" (doc-comment outer " doc"))"#
+        )
+    );
+}
@@ -3,55 +3,463 @@
 //! These subcommands are:
 //!  `compare`
 //!  `inspect`
+//!  `check`
+//!  `list-testcases`
+//!  `selfcheck`
 //!  `course`
 
-use crate::cleaning;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use crate::cleaning::{self, CleaningMode};
 use crate::combination;
 use crate::comparison::{
-    compare, regularised_from_lexlucid, regularised_from_rustc, Comparison, Regularisation,
+    compare, regularise_lexlucid_analysis, regularised_from_lexlucid,
+    regularised_from_lexlucid_timed, regularised_from_rustc_timed, rejection_categories_differ,
+    report_token_stream_comparison, Comparison, InputTimings, Regularisation,
 };
 use crate::lex_via_rustc;
-use crate::lexlucid;
-use crate::utils::escape_for_display;
+use crate::lexlucid::{self, RejectionCategory};
+use crate::ra_kinds::format_ra_tokens;
+use crate::regular_tokens::{self, regularise_from_coarse, TokenKind};
+use crate::rustc_recording::RustcRecording;
+use crate::serialization;
+use crate::sexpr::format_sexpr;
+use crate::utils::{escape_for_display, format_represented_char, format_represented_string};
 use crate::Edition;
 
 /// Implements the `compare` (default) CLI command.
-pub fn run_compare_subcommand(
-    inputs: &[&str],
-    edition: Edition,
-    details_mode: DetailsMode,
-    show_failures_only: bool,
-) {
+pub fn run_compare_subcommand(inputs: &[&str], edition: Edition, options: &CompareOptions) {
     let mut passes = 0;
     let mut failures = 0;
     let mut model_errors = 0;
-    for input in inputs {
-        match show_comparison(input, edition, details_mode, show_failures_only) {
+    let mut filtered_out = 0;
+    let mut model_error_tally = ModelErrorTally::default();
+    let mut rejection_tally = RejectionTally::default();
+    let mut slow_inputs = Vec::new();
+    let mut csv_writer = options.csv_path.as_deref().and_then(CsvWriter::create);
+    let mut index = 0;
+    let mut stopped_early = false;
+    compare_inputs(inputs, edition, options, |input, result| {
+        let label = options
+            .input_labels
+            .as_ref()
+            .map(|labels| labels[index].as_str());
+        index += 1;
+        let Some(result) = result else {
+            filtered_out += 1;
+            return ControlFlow::Continue(());
+        };
+        if let Some(writer) = &mut csv_writer {
+            writer.write_row(&[
+                &label.map_or_else(|| escape_for_display(input), str::to_owned),
+                &single_model_symbol(&result.rustc).to_string(),
+                &single_model_symbol(&result.lexlucid).to_string(),
+                match result.comparison {
+                    Comparison::Agree => "agree",
+                    Comparison::Differ => "differ",
+                    Comparison::ModelErrors => "model-error",
+                },
+            ]);
+            if writer.failed {
+                csv_writer = None;
+            }
+        }
+        model_error_tally.record("rustc", &result.rustc);
+        model_error_tally.record("lexlucid", &result.lexlucid);
+        rejection_tally.record(&result.lexlucid);
+        if let Some(threshold) = options.slow_threshold {
+            let elapsed = result.timings.lexlucid_total();
+            if elapsed > threshold {
+                slow_inputs.push((
+                    elapsed,
+                    label.map_or_else(|| format!("«{}»", escape_for_display(input)), str::to_owned),
+                ));
+            }
+        }
+        match result.comparison {
             Comparison::Agree => passes += 1,
             Comparison::Differ => failures += 1,
             Comparison::ModelErrors => model_errors += 1,
         }
+        show_comparison(input, label, edition, options, &result);
+        let failed = !matches!(result.comparison, Comparison::Agree);
+        if options.panic_on_model_error {
+            if let Regularisation::ModelError(messages) = result.lexlucid {
+                panic_with_model_error(input, messages);
+            }
+        }
+        if options.fail_fast && failed {
+            stopped_early = index < inputs.len();
+            return ControlFlow::Break(());
+        }
+        ControlFlow::Continue(())
+    });
+    if !(options.quiet && failures == 0 && model_errors == 0) {
+        print!("\n{passes} passed, {failures} failed");
+        if options.only.is_some() {
+            print!(" ({filtered_out} skipped by --only)");
+        }
+        if stopped_early {
+            print!(" (stopped at the first failure)");
+        }
+        println!();
+        if model_errors != 0 {
+            println!("*** {model_errors} model errors ***");
+        }
+        if options.show_model_error_report {
+            model_error_tally.print_report();
+        }
+        if options.show_rejection_report {
+            rejection_tally.print_report();
+        }
+        if let Some(threshold) = options.slow_threshold {
+            print_slow_inputs(threshold, &mut slow_inputs);
+        }
+    }
+}
+
+/// Prints the inputs which took lexlucid longer than `threshold`, slowest first.
+fn print_slow_inputs(threshold: Duration, slow_inputs: &mut [(Duration, String)]) {
+    println!("\nInputs lexlucid took longer than {threshold:?} to lex:");
+    if slow_inputs.is_empty() {
+        println!("  (none)");
+    }
+    slow_inputs.sort_by(|(elapsed1, _), (elapsed2, _)| elapsed2.cmp(elapsed1));
+    for (elapsed, input) in slow_inputs.iter() {
+        println!("  {:>10}  {input}", format!("{elapsed:.3?}"));
     }
-    println!("\n{passes} passed, {failures} failed");
-    if model_errors != 0 {
-        println!("*** {model_errors} model errors ***");
+}
+
+/// Writes the rows of `compare`'s CSV output as they're produced.
+///
+/// Each row is flushed as soon as it's written, so that a long run doesn't hold its results in
+/// memory (and an interrupted run leaves the rows for the inputs it reached).
+struct CsvWriter<W: Write> {
+    out: std::io::BufWriter<W>,
+    path: PathBuf,
+    /// Set after a write fails (the failure has been reported, and no more rows are written).
+    failed: bool,
+}
+
+impl CsvWriter<std::fs::File> {
+    /// Creates the file at `path` and writes the header row, or reports why it can't.
+    fn create(path: &Path) -> Option<Self> {
+        match std::fs::File::create(path) {
+            Ok(file) => {
+                let mut writer = CsvWriter::new(file, path);
+                writer.write_row(&["input", "rustc", "lexlucid", "comparison"]);
+                Some(writer)
+            }
+            Err(e) => {
+                eprintln!("can't write {}: {e}", path.display());
+                None
+            }
+        }
+    }
+}
+
+impl<W: Write> CsvWriter<W> {
+    fn new(out: W, path: &Path) -> Self {
+        Self {
+            out: std::io::BufWriter::new(out),
+            path: path.to_owned(),
+            failed: false,
+        }
+    }
+
+    /// Writes and flushes a row, reporting the error if that fails.
+    fn write_row(&mut self, fields: &[&str]) {
+        if self.failed {
+            return;
+        }
+        let result = self
+            .out
+            .write_all(csv_row(fields).as_bytes())
+            .and_then(|_| self.out.flush());
+        if let Err(e) = result {
+            eprintln!("can't write {}: {e}", self.path.display());
+            self.failed = true;
+        }
     }
 }
 
+/// Formats a line of CSV (as described by RFC 4180), quoting every field.
+fn csv_row(fields: &[&str]) -> String {
+    let quoted: Vec<_> = fields
+        .iter()
+        .map(|field| format!("\"{}\"", field.replace('"', "\"\"")))
+        .collect();
+    format!("{}\r\n", quoted.join(","))
+}
+
 /// Implements the `inspect` CLI command.
-pub fn run_inspect_subcommand(inputs: &[&str], edition: Edition) {
+pub fn run_inspect_subcommand(inputs: &[&str], edition: Edition, options: &InspectOptions) {
+    let InspectOptions {
+        format,
+        show_timings,
+        cleaning_mode,
+        panic_on_model_error,
+        all_editions,
+        explain,
+    } = *options;
     for input in inputs {
-        show_detail(input, edition);
-        println!();
+        match format {
+            InspectFormat::Text if all_editions => {
+                show_all_editions(input, cleaning_mode, panic_on_model_error);
+                println!();
+            }
+            InspectFormat::Text => {
+                show_detail(
+                    input,
+                    edition,
+                    show_timings,
+                    cleaning_mode,
+                    panic_on_model_error,
+                    explain,
+                );
+                println!();
+            }
+            InspectFormat::Json => show_json(input, edition, cleaning_mode, panic_on_model_error),
+            InspectFormat::RaKinds => {
+                show_formatted(
+                    input,
+                    edition,
+                    cleaning_mode,
+                    panic_on_model_error,
+                    format_ra_tokens,
+                );
+                println!();
+            }
+            InspectFormat::Sexpr => {
+                show_formatted(
+                    input,
+                    edition,
+                    cleaning_mode,
+                    panic_on_model_error,
+                    format_sexpr,
+                );
+                println!();
+            }
+        }
+    }
+}
+
+/// Prints an input and the messages describing the model error lexlucid reported for it, then
+/// panics.
+///
+/// This is for `--panic-on-model-error`: running with `RUST_BACKTRACE=1` then shows how the
+/// model error was reached.
+fn panic_with_model_error(input: &str, messages: Vec<String>) -> ! {
+    eprintln!(
+        "lexlucid reported a model error for «{}»",
+        escape_for_display(input)
+    );
+    for message in messages {
+        eprintln!("  {message}");
+    }
+    panic!("lexlucid reported a model error (--panic-on-model-error)");
+}
+
+/// Implements the `tokenize-file` CLI command.
+///
+/// Prints lexlucid's fine-grained tokens for the contents of the file at `path`.
+///
+/// Returns the exit status: 0 if lexlucid accepted the input, 1 if it rejected it, 3 if it reported
+/// a model error, and 4 if the file couldn't be read as UTF-8 text.
+pub fn run_tokenize_file_subcommand(
+    path: &Path,
+    edition: Edition,
+    format: InspectFormat,
+    cleaning_mode: CleaningMode,
+) -> u8 {
+    let input = match std::fs::read_to_string(path) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("can't read {}: {e}", path.display());
+            return 4;
+        }
+    };
+    let analysis = match clean_and_analyse(&input, edition, cleaning_mode, false) {
+        Ok(analysis) => analysis,
+        Err(message) => {
+            match format {
+                InspectFormat::Json => println!(
+                    "{}",
+                    serialization::cleaning_rejection_as_json(&input, &message)
+                ),
+                InspectFormat::Text | InspectFormat::RaKinds | InspectFormat::Sexpr => {
+                    eprintln!("lexlucid: rejected");
+                    eprintln!("  error: {message}");
+                }
+            }
+            return 1;
+        }
+    };
+    let status = match analysis {
+        lexlucid::Analysis::Accepts(..) => 0,
+        lexlucid::Analysis::Rejects(..) => 1,
+        lexlucid::Analysis::ModelError(_) => 3,
+    };
+    match format {
+        InspectFormat::Json => println!("{}", serialization::analysis_as_json(&input, analysis)),
+        InspectFormat::Text | InspectFormat::RaKinds | InspectFormat::Sexpr => match analysis {
+            lexlucid::Analysis::Accepts(_, tokens) if format == InspectFormat::Sexpr => {
+                println!("{}", format_sexpr(&tokens));
+            }
+            lexlucid::Analysis::Accepts(_, tokens) if format == InspectFormat::RaKinds => {
+                println!("{}", format_ra_tokens(&tokens));
+            }
+            lexlucid::Analysis::Accepts(_, tokens) => {
+                for token in tokens {
+                    println!("{}", format_token(&token));
+                }
+            }
+            lexlucid::Analysis::Rejects(category, reason) => {
+                eprintln!("lexlucid: rejected ({category:?})");
+                for s in reason.into_description() {
+                    eprintln!("  error: {s}");
+                }
+            }
+            lexlucid::Analysis::ModelError(reason) => {
+                eprintln!("lexlucid: reported a bug in its model");
+                for s in reason.into_description() {
+                    eprintln!("  error: {s}");
+                }
+            }
+        },
     }
+    status
+}
+
+/// Implements the `check` CLI command.
+///
+/// Lexes the input with lexlucid (after the default cleaning), and checks whether it accepts the
+/// input if `expect_accept` is true, or rejects it otherwise. Prints nothing if the check passes.
+///
+/// Returns the exit status: 0 if the check passed, 1 if it failed, and 3 if lexlucid reported a
+/// model error.
+pub fn run_check_subcommand(input: &str, edition: Edition, expect_accept: bool) -> u8 {
+    let cleaned = cleaning::clean(input);
+    let accepted = match lexlucid::analyse(&cleaned, edition) {
+        lexlucid::Analysis::Accepts(..) => true,
+        lexlucid::Analysis::Rejects(category, reason) => {
+            if expect_accept {
+                eprintln!("lexlucid: rejected ({category:?})");
+                for s in reason.into_description() {
+                    eprintln!("  error: {s}");
+                }
+            }
+            false
+        }
+        lexlucid::Analysis::ModelError(reason) => {
+            eprintln!("lexlucid: reported a bug in its model");
+            for s in reason.into_description() {
+                eprintln!("  error: {s}");
+            }
+            return 3;
+        }
+    };
+    if accepted == expect_accept {
+        return 0;
+    }
+    if accepted {
+        eprintln!("lexlucid: accepted");
+    }
+    eprintln!(
+        "expected lexlucid to {} «{}»",
+        if expect_accept { "accept" } else { "reject" },
+        escape_for_display(input)
+    );
+    1
+}
+
+/// Implements the `list-testcases` CLI command.
+///
+/// Prints each input on a line of its own, escaped as in the other reports (or as a JSON string if
+/// `format` is `Json`), without lexing it.
+pub fn run_list_testcases_subcommand(inputs: &[&str], format: InspectFormat) {
+    for input in inputs {
+        match format {
+            InspectFormat::Json => println!("{}", serialization::input_as_json(input)),
+            _ => println!("{}", escape_for_display(input)),
+        }
+    }
+}
+
+/// Implements the `selfcheck` CLI command.
+///
+/// Lexes each input with lexlucid (in every edition if `all_editions` is true), and reports each
+/// input for which lexlucid reports a model error. Model errors should never happen for the
+/// curated testcases.
+///
+/// Returns the exit status: 0 if there were no model errors, and 3 otherwise.
+pub fn run_selfcheck_subcommand(inputs: &[&str], edition: Edition, all_editions: bool) -> u8 {
+    let editions = if all_editions {
+        Edition::ALL.as_slice()
+    } else {
+        std::slice::from_ref(&edition)
+    };
+    let mut model_error_tally = ModelErrorTally::default();
+    let mut model_errors = 0;
+    for input in inputs {
+        for &edition in editions {
+            let regularisation = regularised_from_lexlucid(input, edition);
+            if let Regularisation::ModelError(messages) = &regularisation {
+                model_errors += 1;
+                println!(
+                    "model error ({edition:?}) for «{}»",
+                    escape_for_display(input)
+                );
+                for message in messages {
+                    println!("  {message}");
+                }
+            }
+            model_error_tally.record("lexlucid", &regularisation);
+        }
+    }
+    let checks = inputs.len() * editions.len();
+    if model_errors == 0 {
+        println!("no model errors in {checks} checks");
+        return 0;
+    }
+    println!("\n*** {model_errors} model errors in {checks} checks ***");
+    model_error_tally.print_report();
+    3
 }
 
 /// Implements the `coarse` CLI command.
-pub fn run_coarse_subcommand(inputs: &[&str], edition: Edition) {
+///
+/// If `count_tokens` is true, prints counts of each kind of coarse token rather than the tokens
+/// themselves.
+///
+/// If `roundtrip` is true, also checks that lexing the coarse tokens' extents (see
+/// [`combination::restringify`]) gives the same coarse tokens, and reports the inputs for which
+/// it doesn't.
+pub fn run_coarse_subcommand(
+    inputs: &[&str],
+    edition: Edition,
+    count_tokens: bool,
+    roundtrip: bool,
+) {
+    let mut roundtrip_failures = Vec::new();
     for input in inputs {
-        show_coarse(input, edition);
+        if !show_coarse(input, edition, count_tokens, roundtrip) {
+            roundtrip_failures.push(*input);
+        }
         println!();
     }
+    if roundtrip {
+        println!("{} inputs failed the round trip", roundtrip_failures.len());
+        for input in roundtrip_failures {
+            println!("  «{}»", escape_for_display(input));
+        }
+    }
 }
 
 #[derive(Copy, Clone, PartialEq, Eq)]
@@ -61,109 +469,591 @@ pub enum DetailsMode {
     Always,
 }
 
+/// Which parts of the regularised tokens `compare` compares.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum DiffKind {
+    /// Compare every token, including its extent and spacing
+    Everything,
+    /// Compare only the represented values of string-family literals (see
+    /// [`regular_tokens::literal_values`])
+    LiteralValues,
+}
+
+/// Options for the `compare` CLI command.
+pub struct CompareOptions {
+    pub details_mode: DetailsMode,
+    pub diff_kind: DiffKind,
+    /// Don't show inputs where rustc and lexlucid agree.
+    pub show_failures_only: bool,
+    /// Don't show inputs where rustc and lexlucid agree, and print nothing at all if they agree on
+    /// every input.
+    pub quiet: bool,
+    /// Show a summary of model errors after the results.
+    pub show_model_error_report: bool,
+    /// Show a summary of lexlucid's reasons for rejecting inputs after the results.
+    pub show_rejection_report: bool,
+    /// Show how long each stage of processing took for each input.
+    pub show_timings: bool,
+    /// After the results, list the inputs for which lexlucid's processing took longer than this.
+    pub slow_threshold: Option<Duration>,
+    /// Only consider inputs whose first token has this kind.
+    ///
+    /// The first token is taken from lexlucid's output if it accepted the input, and otherwise from
+    /// rustc's. Inputs which neither accepts are skipped.
+    pub only: Option<TokenKind>,
+    /// Number of worker threads to lex the inputs with.
+    pub jobs: usize,
+    /// Treat inputs which both reject as disagreeing if the categories of rejection differ.
+    pub same_rejection_category: bool,
+    /// Panic after showing the first input for which lexlucid reports a model error.
+    pub panic_on_model_error: bool,
+    /// Stop after showing the first input for which rustc and lexlucid don't agree.
+    pub fail_fast: bool,
+    /// Write a CSV file summarising the results to this path.
+    pub csv_path: Option<PathBuf>,
+    /// A name for each input (such as the file it was read from), to show instead of the input's
+    /// text.
+    pub input_labels: Option<Vec<String>>,
+    /// Take rustc's output from this recording (see [`crate::rustc_recording`]) rather than
+    /// running rustc.
+    pub replay: Option<RustcRecording>,
+}
+
+/// Options for the `inspect` CLI command.
+#[derive(Copy, Clone)]
+pub struct InspectOptions {
+    pub format: InspectFormat,
+    /// Show how long each stage of processing took (ignored when `format` is `Json`).
+    pub show_timings: bool,
+    /// How to clean lexlucid's input (rustc's input is unaffected).
+    pub cleaning_mode: CleaningMode,
+    /// Panic (see [`panic_with_model_error`]) after showing the first input for which lexlucid
+    /// reports a model error.
+    pub panic_on_model_error: bool,
+    /// Lex each input with lexlucid in every edition, instead of comparing with rustc in a single
+    /// edition (only supported when `format` is `Text`).
+    pub all_editions: bool,
+    /// Show which pretokenisation rule produced each token (only supported when `format` is `Text`).
+    pub explain: bool,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum InspectFormat {
+    /// Human-readable description of both rustc's and lexlucid's output
+    Text,
+    /// One line of JSON per input, describing lexlucid's output (see `serialization`)
+    Json,
+    /// One line per token, describing lexlucid's output using `rustc_lexer`'s names for token
+    /// kinds (see `ra_kinds`)
+    RaKinds,
+    /// One S-expression per input, describing lexlucid's output with delimited groups nested (see
+    /// `sexpr`)
+    Sexpr,
+}
+
 fn format_pretoken(pretoken: &lexlucid::Pretoken) -> String {
     format!("{:?}, {:?}", pretoken.data, pretoken.extent)
 }
+/// Formats a fine-grained token on a single line.
+///
+/// For byte-string and C-string literals, the represented bytes are shown as hex and ASCII (see
+/// [`format_represented_bytes`]) rather than as decimal numbers. The values of character and string
+/// literals are shown with Rust's debug escapes (see [`format_represented_char`]).
 fn format_token(token: &lexlucid::FineToken) -> String {
-    format!("{:?}, {:?}", token.data, token.extent)
+    use lexlucid::FineTokenData::*;
+    let (variant, represented_bytes, suffix, continuations) = match &token.data {
+        CharacterLiteral {
+            represented_character,
+            suffix,
+        } => {
+            return format!(
+                "CharacterLiteral {{ represented_character: {}, suffix: {:?} }}, {:?}",
+                format_represented_char(*represented_character),
+                suffix,
+                token.extent
+            )
+        }
+        StringLiteral {
+            represented_string,
+            suffix,
+            continuations,
+        } => {
+            return format!(
+            "StringLiteral {{ represented_string: {}, suffix: {:?}, continuations: {:?} }}, {:?}",
+            format_represented_string(&represented_string.to_string()),
+            suffix,
+            continuations,
+            token.extent
+        )
+        }
+        RawStringLiteral {
+            represented_string,
+            suffix,
+        } => {
+            return format!(
+                "RawStringLiteral {{ represented_string: {}, suffix: {:?} }}, {:?}",
+                format_represented_string(&represented_string.to_string()),
+                suffix,
+                token.extent
+            )
+        }
+        ByteStringLiteral {
+            represented_bytes,
+            suffix,
+            continuations,
+        } => (
+            "ByteStringLiteral",
+            represented_bytes,
+            suffix,
+            Some(continuations),
+        ),
+        RawByteStringLiteral {
+            represented_bytes,
+            suffix,
+        } => ("RawByteStringLiteral", represented_bytes, suffix, None),
+        CStringLiteral {
+            represented_bytes,
+            suffix,
+            continuations,
+        } => (
+            "CStringLiteral",
+            represented_bytes,
+            suffix,
+            Some(continuations),
+        ),
+        RawCStringLiteral {
+            represented_bytes,
+            suffix,
+        } => ("RawCStringLiteral", represented_bytes, suffix, None),
+        _ => return format!("{:?}, {:?}", token.data, token.extent),
+    };
+    let continuations = match continuations {
+        Some(continuations) => format!(", continuations: {continuations:?}"),
+        None => String::new(),
+    };
+    format!(
+        "{variant} {{ represented_bytes: {}, suffix: {:?}{continuations} }}, {:?}",
+        format_represented_bytes(represented_bytes),
+        suffix,
+        token.extent
+    )
+}
+
+/// Formats a sequence of bytes like a line of a hex dump.
+///
+/// For example `b"ab\n"` is shown as `[61 62 0a] |ab.|`: each byte in hex, followed by the bytes
+/// as ASCII with `.` standing for anything which isn't printable.
+fn format_represented_bytes(bytes: &[u8]) -> String {
+    let hex: Vec<_> = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    let ascii: String = bytes
+        .iter()
+        .map(|&b| {
+            if b.is_ascii_graphic() || b == b' ' {
+                b as char
+            } else {
+                '.'
+            }
+        })
+        .collect();
+    format!("[{}] |{ascii}|", hex.join(" "))
 }
+/// Formats a coarse-grained token on a single line.
+///
+/// The values of character and string literals are shown as in [`format_token`].
 fn format_coarse_token(ctoken: &combination::CoarseToken) -> String {
-    format!("{:?}, {:?}", ctoken.data, ctoken.extent)
+    use combination::CoarseTokenData::*;
+    let data = match &ctoken.data {
+        CharacterLiteral {
+            represented_character,
+            suffix,
+        } => format!(
+            "CharacterLiteral {{ represented_character: {}, suffix: {:?} }}",
+            format_represented_char(*represented_character),
+            suffix
+        ),
+        StringLiteral {
+            represented_string,
+            suffix,
+        } => format!(
+            "StringLiteral {{ represented_string: {}, suffix: {:?} }}",
+            format_represented_string(&represented_string.to_string()),
+            suffix
+        ),
+        RawStringLiteral {
+            represented_string,
+            suffix,
+        } => format!(
+            "RawStringLiteral {{ represented_string: {}, suffix: {:?} }}",
+            format_represented_string(&represented_string.to_string()),
+            suffix
+        ),
+        data => format!("{data:?}"),
+    };
+    format!("{data}, {:?}", ctoken.extent)
 }
 
 /// Returns a symbol indicating how a single model responded to the input.
 fn single_model_symbol(reg: &Regularisation) -> char {
     match reg {
         Regularisation::Accepts(_) => '✓',
-        Regularisation::Rejects(_) => '✗',
+        Regularisation::Rejects(..) => '✗',
         Regularisation::ModelError(_) => '💣',
     }
 }
 
-/// Compares 'regularised' tokens from rustc and lexlucid.
+/// Counts of model errors seen during a run, grouped by model and by message.
+#[derive(Default)]
+struct ModelErrorTally {
+    counts: BTreeMap<(&'static str, String), usize>,
+}
+
+impl ModelErrorTally {
+    /// Records the model error (if any) reported by the named model.
+    fn record(&mut self, model: &'static str, reg: &Regularisation) {
+        if let Regularisation::ModelError(messages) = reg {
+            let message = messages
+                .first()
+                .map(|msg| normalise_model_error_message(msg))
+                .unwrap_or_else(|| "(no message)".into());
+            *self.counts.entry((model, message)).or_insert(0) += 1;
+        }
+    }
+
+    /// Prints the counts, most frequent first.
+    fn print_report(&self) {
+        println!("\nModel errors by message:");
+        if self.counts.is_empty() {
+            println!("  (none)");
+            return;
+        }
+        let mut counts: Vec<_> = self.counts.iter().collect();
+        counts.sort_by(|(_, count1), (_, count2)| count2.cmp(count1));
+        for ((model, message), count) in counts {
+            println!("  {count:5}  {model}: {message}");
+        }
+    }
+}
+
+/// Counts of lexlucid's rejections seen during a run, grouped by reason and by category.
+#[derive(Default)]
+struct RejectionTally {
+    reasons: BTreeMap<String, usize>,
+    categories: BTreeMap<RejectionCategory, usize>,
+}
+
+impl RejectionTally {
+    /// Records the rejection (if any) in lexlucid's regularisation.
+    fn record(&mut self, reg: &Regularisation) {
+        if let Regularisation::Rejects(category, messages) = reg {
+            let reason = messages
+                .first()
+                .map(|msg| normalise_model_error_message(msg))
+                .unwrap_or_else(|| "(no message)".into());
+            *self.reasons.entry(reason).or_insert(0) += 1;
+            if let Some(category) = category {
+                *self.categories.entry(*category).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// Prints the counts (most frequent first), followed by the categories which were never seen.
+    fn print_report(&self) {
+        println!("\nlexlucid's rejections by reason:");
+        if self.reasons.is_empty() {
+            println!("  (none)");
+        }
+        let mut counts: Vec<_> = self.reasons.iter().collect();
+        counts.sort_by(|(_, count1), (_, count2)| count2.cmp(count1));
+        for (reason, count) in counts {
+            println!("  {count:5}  {reason}");
+        }
+        let unseen: Vec<_> = RejectionCategory::ALL
+            .iter()
+            .filter(|category| !self.categories.contains_key(category))
+            .collect();
+        if !unseen.is_empty() {
+            println!("Rejection categories never used:");
+            for category in unseen {
+                println!("  {category:?}");
+            }
+        }
+    }
+}
+
+/// Removes input-specific detail from a model error or rejection message, so that similar messages
+/// are grouped.
+///
+/// This replaces anything between «» (which is how we show fragments of the input) with `…`.
+fn normalise_model_error_message(message: &str) -> String {
+    let mut normalised = String::new();
+    let mut in_fragment = false;
+    for c in message.chars() {
+        match c {
+            '«' => {
+                in_fragment = true;
+                normalised.push_str("«…");
+            }
+            '»' => {
+                in_fragment = false;
+                normalised.push(c);
+            }
+            _ if in_fragment => {}
+            _ => normalised.push(c),
+        }
+    }
+    normalised
+}
+
+/// The result of lexing a single input with both rustc and lexlucid.
+struct InputComparison {
+    rustc: Regularisation,
+    lexlucid: Regularisation,
+    comparison: Comparison,
+    timings: InputTimings,
+}
+
+/// Stack size for the worker threads used by `compare_inputs`.
+///
+/// This matches the main thread's usual stack size, as rustc's token tree construction recurses
+/// for each level of delimiter nesting.
+const WORKER_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+/// Lexes each input with both rustc and lexlucid, and passes the results to `report`.
+///
+/// If `options.jobs` is more than 1, the inputs are lexed using that many worker threads (rustc's
+/// session globals are thread-local, so each worker has its own). Either way, `report` is called
+/// from the current thread, once for each input, in input order.
+///
+/// The result passed to `report` is None if the input is skipped because of `options.only`.
+///
+/// If `report` returns `Break`, no further inputs are reported (and no more are lexed, apart from
+/// any which worker threads have already started on).
+fn compare_inputs(
+    inputs: &[&str],
+    edition: Edition,
+    options: &CompareOptions,
+    mut report: impl FnMut(&str, Option<InputComparison>) -> ControlFlow<()>,
+) {
+    if options.jobs <= 1 {
+        for input in inputs {
+            if report(input, compare_input(input, edition, options)).is_break() {
+                return;
+            }
+        }
+        return;
+    }
+    let next_index = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    std::thread::scope(|scope| {
+        for _ in 0..options.jobs {
+            let sender = sender.clone();
+            let next_index = &next_index;
+            std::thread::Builder::new()
+                .stack_size(WORKER_STACK_SIZE)
+                .spawn_scoped(scope, move || loop {
+                    let index = next_index.fetch_add(1, Ordering::Relaxed);
+                    let Some(input) = inputs.get(index) else {
+                        break;
+                    };
+                    if sender
+                        .send((index, compare_input(input, edition, options)))
+                        .is_err()
+                    {
+                        break;
+                    }
+                })
+                .expect("failed to spawn worker thread");
+        }
+        drop(sender);
+        // Results arrive in whatever order the workers finish them; hold on to each one until
+        // everything before it has been reported.
+        let mut pending = BTreeMap::new();
+        let mut next_to_report = 0;
+        'receiving: for (index, result) in receiver {
+            pending.insert(index, result);
+            while let Some(result) = pending.remove(&next_to_report) {
+                if report(inputs[next_to_report], result).is_break() {
+                    // Stop the workers from taking any more inputs
+                    next_index.store(inputs.len(), Ordering::Relaxed);
+                    break 'receiving;
+                }
+                next_to_report += 1;
+            }
+        }
+    });
+}
+
+/// Lexes an input with both rustc and lexlucid, and compares the 'regularised' tokens.
+///
+/// Returns None if the input is skipped because of `options.only`.
+fn compare_input(
+    input: &str,
+    edition: Edition,
+    options: &CompareOptions,
+) -> Option<InputComparison> {
+    let mut timings = InputTimings::default();
+    let rustc = match &options.replay {
+        Some(recording) => recording.regularisation(input),
+        None => regularised_from_rustc_timed(input, edition, &mut timings),
+    };
+    let lexlucid = regularised_from_lexlucid_timed(input, edition, &mut timings);
+    if let Some(wanted_kind) = options.only {
+        if first_token_kind(&lexlucid).or_else(|| first_token_kind(&rustc)) != Some(wanted_kind) {
+            return None;
+        }
+    }
+    let (rustc, lexlucid) = match options.diff_kind {
+        DiffKind::Everything => (rustc, lexlucid),
+        DiffKind::LiteralValues => (literal_values_only(rustc), literal_values_only(lexlucid)),
+    };
+    let mut comparison = compare(&rustc, &lexlucid);
+    if options.same_rejection_category && rejection_categories_differ(&rustc, &lexlucid) {
+        comparison = Comparison::Differ;
+    }
+    Some(InputComparison {
+        rustc,
+        lexlucid,
+        comparison,
+        timings,
+    })
+}
+
+/// Shows the result of comparing 'regularised' tokens from rustc and lexlucid.
 ///
 /// Shows whether the tokenisations match.
 /// May also show detail, depending on `details_mode`.
 ///
-/// Returns the result of the comparison.
+/// Shows how long each stage took if `show_timings` is true.
+///
+/// Identifies the input by `label` if there is one, and otherwise by its text.
 fn show_comparison(
     input: &str,
+    label: Option<&str>,
     edition: Edition,
-    details_mode: DetailsMode,
-    show_failures_only: bool,
-) -> Comparison {
-    let rustc = regularised_from_rustc(input, edition);
-    let lexlucid = regularised_from_lexlucid(input, edition);
-    let comparison = compare(&rustc, &lexlucid);
-
+    options: &CompareOptions,
+    result: &InputComparison,
+) {
+    let InputComparison {
+        rustc,
+        lexlucid,
+        comparison,
+        timings,
+    } = result;
     let passes = matches!(comparison, Comparison::Agree);
-    if passes && show_failures_only {
-        return comparison;
+    if passes && (options.show_failures_only || options.quiet) {
+        return;
     }
+    let details_mode = options.details_mode;
     let show_detail = (details_mode == DetailsMode::Always)
         || ((details_mode == DetailsMode::Failures) && !passes);
 
     println!(
-        "{} R:{} L:{} «{}»",
+        "{} R:{} L:{} {}",
         match comparison {
             Comparison::Agree => '✔',
             Comparison::Differ => '‼',
             Comparison::ModelErrors => '💣',
         },
-        single_model_symbol(&rustc),
-        single_model_symbol(&lexlucid),
-        escape_for_display(input)
+        single_model_symbol(rustc),
+        single_model_symbol(lexlucid),
+        match label {
+            Some(label) => label.to_owned(),
+            None => format!("«{}»", escape_for_display(input)),
+        }
     );
+    if options.show_timings {
+        println!("  timings: {}", timings.describe());
+    }
 
     if show_detail {
-        match rustc {
-            Regularisation::Accepts(tokens) => {
-                println!("  rustc: accepted");
-                for token in tokens {
-                    println!("    {:?}", token);
-                }
-            }
-            Regularisation::Rejects(messages) => {
-                println!("  rustc: rejected");
-                for msg in messages {
-                    println!("    {msg}");
-                }
-            }
-            Regularisation::ModelError(messages) => {
-                println!("  rustc: reported model error");
-                for msg in messages {
-                    println!("    {msg}");
-                }
-            }
+        let report = report_token_stream_comparison("rustc", rustc, "lexlucid", lexlucid);
+        for line in report.lines {
+            println!("  {line}");
+        }
+        if options.same_rejection_category && rejection_categories_differ(rustc, lexlucid) {
+            println!("  rejection categories differ");
+        }
+    }
+    if details_mode == DetailsMode::Always {
+        show_string_continuations(input, edition);
+    }
+}
+
+/// Reduces the tokens of a regularisation which accepted the input to literal values.
+fn literal_values_only(regularisation: Regularisation) -> Regularisation {
+    match regularisation {
+        Regularisation::Accepts(tokens) => {
+            Regularisation::Accepts(regular_tokens::literal_values(tokens))
+        }
+        other => other,
+    }
+}
+
+/// Returns the kind of the first token in a regularisation, if it accepted the input.
+fn first_token_kind(regularisation: &Regularisation) -> Option<TokenKind> {
+    match regularisation {
+        Regularisation::Accepts(tokens) => tokens.first().and_then(|token| token.data.kind()),
+        _ => None,
+    }
+}
+
+/// Prints the string continuation escapes lexlucid found in string, byte string, and C string
+/// literals, if there are any.
+///
+/// The regularised tokens don't include this information, because rustc reports it only as
+/// warnings.
+fn show_string_continuations(input: &str, edition: Edition) {
+    let cleaned = cleaning::clean(input);
+    let lexlucid::Analysis::Accepts(_, tokens) = lexlucid::analyse(&cleaned, edition) else {
+        return;
+    };
+    let mut first = true;
+    for token in tokens {
+        let (lexlucid::FineTokenData::StringLiteral { continuations, .. }
+        | lexlucid::FineTokenData::ByteStringLiteral { continuations, .. }
+        | lexlucid::FineTokenData::CStringLiteral { continuations, .. }) = &token.data
+        else {
+            continue;
         };
-        match lexlucid {
-            Regularisation::Accepts(tokens) => {
-                println!("  lexlucid: accepted");
-                for token in tokens {
-                    println!("    {:?}", token);
-                }
+        for continuation in continuations {
+            if first {
+                println!("  lexlucid: string continuations");
+                first = false;
             }
-            Regularisation::Rejects(messages) => {
-                println!("  lexlucid: rejected");
-                for msg in messages {
-                    println!("    {msg}");
+            let start = token.span.start + continuation.skipped.start;
+            let end = token.span.start + continuation.skipped.end;
+            println!(
+                "    «{}»{}",
+                escape_for_display(&cleaned[start..end]),
+                if continuation.skips_multiple_lines {
+                    " (skips multiple lines)"
+                } else {
+                    ""
                 }
-            }
-            Regularisation::ModelError(messages) => {
-                println!("  lexlucid: reported a bug in its model");
-                for msg in messages {
-                    println!("    {msg}");
-                }
-            }
+            );
         }
     }
-    comparison
 }
 
 /// Lexes with both rustc and lexlucid, and prints the results.
-fn show_detail(input: &str, edition: Edition) {
+///
+/// Also prints how long each stage took if `show_timings` is true.
+///
+/// `cleaning_mode` affects only lexlucid's input.
+fn show_detail(
+    input: &str,
+    edition: Edition,
+    show_timings: bool,
+    cleaning_mode: CleaningMode,
+    panic_on_model_error: bool,
+    explain: bool,
+) {
     println!("Lexing «{}»", escape_for_display(input));
-    match lex_via_rustc::analyse(input, edition) {
+    let mut timings = InputTimings::default();
+    let start = Instant::now();
+    let rustc_analysis = lex_via_rustc::analyse(input, edition);
+    timings.rustc = Some(start.elapsed());
+    match rustc_analysis {
         lex_via_rustc::Analysis::Accepts(tokens) => {
             println!("rustc: accepted");
             for token in tokens {
@@ -186,21 +1076,45 @@ fn show_detail(input: &str, edition: Edition) {
             println!("rustc: internal compiler error");
         }
     }
-    let cleaned = cleaning::clean(input);
-    match lexlucid::analyse(&cleaned, edition) {
+    let start = Instant::now();
+    let cleaning_result = cleaning::clean_with_report(input, cleaning_mode);
+    timings.cleaning = start.elapsed();
+    let (cleaned, cleaning_report) = match cleaning_result {
+        Ok(cleaned) => cleaned,
+        Err(message) => {
+            println!("lexlucid: rejected in cleaning");
+            println!("  error: {message}");
+            return;
+        }
+    };
+    for line in cleaning_report.describe() {
+        println!("cleaning: {line}");
+    }
+    let (analysis, step_timings) = lexlucid::analyse_timed(&cleaned, edition);
+    timings.pretokenisation = step_timings.pretokenisation;
+    timings.reprocessing = step_timings.reprocessing;
+    match analysis {
         lexlucid::Analysis::Accepts(pretokens, tokens) => {
             println!("lexlucid: accepted");
             println!("  -- pretokens --");
-            for pretoken in pretokens {
-                println!("  {}", format_pretoken(&pretoken));
+            for pretoken in pretokens.iter() {
+                println!("  {}", format_pretoken(pretoken));
             }
             println!("  -- tokens --");
-            for token in tokens {
-                println!("  {}", format_token(&token));
+            for (token, pretoken) in tokens.iter().zip(pretokens.iter()) {
+                if explain {
+                    println!("  {}  [rule: {:?}]", format_token(token), pretoken.rule);
+                } else {
+                    println!("  {}", format_token(token));
+                }
             }
         }
-        lexlucid::Analysis::Rejects(lexlucid::Reason::Pretokenisation(messages, pretokens, _)) => {
+        lexlucid::Analysis::Rejects(
+            category,
+            lexlucid::Reason::Pretokenisation(messages, pretokens, _),
+        ) => {
             println!("lexlucid: rejected in step 1 (pretokenisation)");
+            println!("  category: {category:?}");
             for message in messages {
                 println!("  error: {message}");
             }
@@ -209,13 +1123,12 @@ fn show_detail(input: &str, edition: Edition) {
                 println!("  {}", format_pretoken(&pretoken));
             }
         }
-        lexlucid::Analysis::Rejects(lexlucid::Reason::Reprocessing(
-            message,
-            rejected,
-            pretokens,
-            tokens,
-        )) => {
+        lexlucid::Analysis::Rejects(
+            category,
+            lexlucid::Reason::Reprocessing(message, rejected, pretokens, tokens),
+        ) => {
             println!("lexlucid: rejected in step 2 (reprocessing)");
+            println!("  category: {category:?}");
             println!("  error: {message}");
             println!("  -- rejected pretoken: --");
             println!("  {}", format_pretoken(&rejected));
@@ -230,30 +1143,179 @@ fn show_detail(input: &str, edition: Edition) {
         }
         lexlucid::Analysis::ModelError(reason) => {
             println!("lexlucid: reported a bug in its model");
-            for s in reason.into_description() {
+            let messages = reason.into_description();
+            for s in messages.iter() {
                 println!("  error: {}", s);
             }
+            if panic_on_model_error {
+                panic_with_model_error(input, messages);
+            }
+        }
+    }
+    if show_timings {
+        println!("timings: {}", timings.describe());
+    }
+}
+
+/// Lexes with lexlucid in each edition, and prints the results together.
+///
+/// Marks the first point where the accepted editions' tokens diverge.
+fn show_all_editions(input: &str, cleaning_mode: CleaningMode, panic_on_model_error: bool) {
+    println!("Lexing «{}» in all editions", escape_for_display(input));
+    let cleaned = match cleaning::clean_with_report(input, cleaning_mode) {
+        Ok((cleaned, _)) => cleaned,
+        Err(message) => {
+            println!("lexlucid: rejected in cleaning");
+            println!("  error: {message}");
+            return;
+        }
+    };
+    // For each edition, the formatted tokens if lexlucid accepted the input
+    let mut results = Vec::new();
+    for edition in Edition::ALL {
+        let analysis = lexlucid::analyse(&cleaned, edition);
+        if panic_on_model_error {
+            if let lexlucid::Analysis::ModelError(reason) = analysis {
+                panic_with_model_error(input, reason.into_description());
+            }
         }
+        results.push((edition, analysis));
+    }
+    let token_lines: Vec<Option<Vec<String>>> = results
+        .iter()
+        .map(|(_, analysis)| match analysis {
+            lexlucid::Analysis::Accepts(_, tokens) => {
+                Some(tokens.iter().map(format_token).collect())
+            }
+            _ => None,
+        })
+        .collect();
+    let accepted: Vec<&Vec<String>> = token_lines.iter().flatten().collect();
+    let accepted_count = accepted.len();
+    let divergence = accepted.first().and_then(|first| {
+        let longest = accepted.iter().map(|lines| lines.len()).max().unwrap();
+        (0..longest).find(|&i| accepted.iter().any(|lines| lines.get(i) != first.get(i)))
+    });
+
+    for ((edition, analysis), lines) in results.into_iter().zip(token_lines) {
+        match analysis {
+            lexlucid::Analysis::Accepts(..) => {
+                println!("{edition:?}: accepted");
+                for (i, line) in lines.unwrap().iter().enumerate() {
+                    if Some(i) == divergence {
+                        println!("  ---- editions diverge here ----");
+                    }
+                    println!("  {line}");
+                }
+            }
+            lexlucid::Analysis::Rejects(category, reason) => {
+                println!("{edition:?}: rejected ({category:?})");
+                for message in reason.into_description() {
+                    println!("  error: {message}");
+                }
+            }
+            lexlucid::Analysis::ModelError(reason) => {
+                println!("{edition:?}: reported a bug in its model");
+                for message in reason.into_description() {
+                    println!("  error: {message}");
+                }
+            }
+        }
+    }
+    let verdicts_agree = accepted_count == 0 || accepted_count == Edition::ALL.len();
+    match divergence {
+        Some(i) => println!("editions diverge at token {i}"),
+        None if verdicts_agree => println!("editions agree"),
+        None => println!("editions differ in whether they accept the input"),
     }
 }
 
-fn show_coarse(input: &str, edition: Edition) {
+/// Cleans the input and runs lexlucid on the result.
+///
+/// Returns the message describing why cleaning rejected the input, if it did.
+///
+/// If `panic_on_model_error` is true and lexlucid reports a model error, panics (see
+/// [`panic_with_model_error`]).
+fn clean_and_analyse(
+    input: &str,
+    edition: Edition,
+    cleaning_mode: CleaningMode,
+    panic_on_model_error: bool,
+) -> Result<lexlucid::Analysis, String> {
+    let (cleaned, _) = cleaning::clean_with_report(input, cleaning_mode)?;
+    let analysis = lexlucid::analyse(&cleaned, edition);
+    if panic_on_model_error {
+        if let lexlucid::Analysis::ModelError(reason) = analysis {
+            panic_with_model_error(input, reason.into_description());
+        }
+    }
+    Ok(analysis)
+}
+
+/// Lexes with lexlucid, and prints the result as a single line of JSON.
+fn show_json(
+    input: &str,
+    edition: Edition,
+    cleaning_mode: CleaningMode,
+    panic_on_model_error: bool,
+) {
+    match clean_and_analyse(input, edition, cleaning_mode, panic_on_model_error) {
+        Ok(analysis) => println!("{}", serialization::analysis_as_json(input, analysis)),
+        Err(message) => println!(
+            "{}",
+            serialization::cleaning_rejection_as_json(input, &message)
+        ),
+    }
+}
+
+/// Lexes with lexlucid, and prints the tokens as described by `format` if it accepts the input.
+///
+/// Otherwise prints only a summary of the rejection or model error.
+fn show_formatted(
+    input: &str,
+    edition: Edition,
+    cleaning_mode: CleaningMode,
+    panic_on_model_error: bool,
+    format: impl Fn(&[lexlucid::FineToken]) -> String,
+) {
+    println!("Lexing «{}»", escape_for_display(input));
+    match clean_and_analyse(input, edition, cleaning_mode, panic_on_model_error) {
+        Ok(lexlucid::Analysis::Accepts(_, tokens)) => println!("{}", format(&tokens)),
+        Ok(lexlucid::Analysis::Rejects(category, _)) => {
+            println!("lexlucid: rejected ({category:?})")
+        }
+        Ok(lexlucid::Analysis::ModelError(_)) => println!("lexlucid: reported a bug in its model"),
+        Err(message) => println!("lexlucid: rejected by cleaning: {message}"),
+    }
+}
+
+/// Returns false if `roundtrip` is true and the round trip check failed.
+fn show_coarse(input: &str, edition: Edition, count_tokens: bool, roundtrip: bool) -> bool {
     println!("Lexing «{}»", escape_for_display(input));
     let cleaned = cleaning::clean(input);
     match lexlucid::analyse(&cleaned, edition) {
         lexlucid::Analysis::Accepts(_, tokens) => {
             println!("lexlucid: accepted");
-            println!("  -- fine-grained --");
-            for token in tokens.iter() {
-                println!("  {}", format_token(token));
+            if !count_tokens {
+                println!("  -- fine-grained --");
+                for token in tokens.iter() {
+                    println!("  {}", format_token(token));
+                }
             }
             let combined = combination::coarsen(tokens);
-            println!("  -- coarse --");
-            for ctoken in combined {
-                println!("  {} {:?}", format_coarse_token(&ctoken), &ctoken.spacing);
+            if count_tokens {
+                show_coarse_token_counts(&combined);
+            } else {
+                println!("  -- coarse --");
+                for ctoken in combined.iter() {
+                    println!("  {} {:?}", format_coarse_token(ctoken), ctoken.spacing);
+                }
+            }
+            if roundtrip {
+                return show_coarse_roundtrip(combined, edition);
             }
         }
-        lexlucid::Analysis::Rejects(reason) => {
+        lexlucid::Analysis::Rejects(_, reason) => {
             println!("lexlucid: rejected");
             for message in reason.into_description() {
                 println!("  {message}");
@@ -266,4 +1328,74 @@ fn show_coarse(input: &str, edition: Edition) {
             }
         }
     }
+    true
+}
+
+/// Lexes the restringified form of the coarse tokens, and reports whether that gives the same
+/// coarse tokens.
+///
+/// Returns true if it does.
+fn show_coarse_roundtrip(ctokens: Vec<combination::CoarseToken>, edition: Edition) -> bool {
+    let restringified = combination::restringify(&ctokens);
+    let original = Regularisation::Accepts(regularise_from_coarse(ctokens));
+    let relexed = regularise_lexlucid_analysis(lexlucid::analyse(&restringified, edition));
+    let report = report_token_stream_comparison("original", &original, "round trip", &relexed);
+    if matches!(report.comparison, Comparison::Agree) {
+        println!("round trip: ok");
+        return true;
+    }
+    println!(
+        "round trip: differs for «{}»",
+        escape_for_display(&restringified)
+    );
+    for line in report.lines {
+        println!("  {line}");
+    }
+    false
+}
+
+/// Prints how many of each kind of coarse token there are, and some totals.
+fn show_coarse_token_counts(ctokens: &[combination::CoarseToken]) {
+    use combination::CoarseTokenData::*;
+    let mut counts: BTreeMap<&'static str, usize> = BTreeMap::new();
+    let mut punctuation_marks = 0;
+    let mut doc_comments = 0;
+    for ctoken in ctokens {
+        let kind = match &ctoken.data {
+            LineComment { .. } => "LineComment",
+            BlockComment { .. } => "BlockComment",
+            Punctuation { .. } => "Punctuation",
+            Identifier { .. } => "Identifier",
+            RawIdentifier { .. } => "RawIdentifier",
+            LifetimeOrLabel { .. } => "LifetimeOrLabel",
+            RawLifetimeOrLabel { .. } => "RawLifetimeOrLabel",
+            ByteLiteral { .. } => "ByteLiteral",
+            ByteStringLiteral { .. } => "ByteStringLiteral",
+            RawByteStringLiteral { .. } => "RawByteStringLiteral",
+            CharacterLiteral { .. } => "CharacterLiteral",
+            StringLiteral { .. } => "StringLiteral",
+            RawStringLiteral { .. } => "RawStringLiteral",
+            CStringLiteral { .. } => "CStringLiteral",
+            RawCStringLiteral { .. } => "RawCStringLiteral",
+            IntegerLiteral { .. } => "IntegerLiteral",
+            FloatLiteral { .. } => "FloatLiteral",
+        };
+        *counts.entry(kind).or_insert(0) += 1;
+        match &ctoken.data {
+            Punctuation { marks } => punctuation_marks += marks.len(),
+            LineComment { .. } | BlockComment { .. } => doc_comments += 1,
+            _ => {}
+        }
+    }
+    let punctuation_tokens = counts.get("Punctuation").copied().unwrap_or(0);
+    println!("  -- coarse token counts --");
+    for (kind, count) in counts {
+        println!("  {count:6}  {kind}");
+    }
+    println!("  {} tokens in total", ctokens.len());
+    println!("  {punctuation_marks} punctuation marks, glued into {punctuation_tokens} tokens");
+    println!("  {doc_comments} doc-comments");
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,61 @@
+use std::path::Path;
+
+use crate::comparison::regularised_from_lexlucid;
+use crate::lexlucid::{self, Analysis, RejectionCategory};
+use crate::testcases::LONGLIST;
+use crate::Edition;
+
+use super::{format_token, run_selfcheck_subcommand, CsvWriter, RejectionTally};
+
+#[test]
+fn csv_rows_are_written_as_they_are_produced() {
+    let mut writer = CsvWriter::new(Vec::new(), Path::new("test.csv"));
+    writer.write_row(&["a", "b\"c"]);
+    assert_eq!(writer.out.get_ref().as_slice(), b"\"a\",\"b\"\"c\"\r\n");
+    writer.write_row(&["d"]);
+    assert_eq!(
+        writer.out.get_ref().as_slice(),
+        b"\"a\",\"b\"\"c\"\r\n\"d\"\r\n"
+    );
+    assert!(!writer.failed);
+}
+
+#[test]
+fn longlist_has_no_model_errors() {
+    assert_eq!(run_selfcheck_subcommand(LONGLIST, Edition::E2021, true), 0);
+}
+
+#[test]
+fn rejections_are_tallied_by_reason_and_category() {
+    let mut tally = RejectionTally::default();
+    for input in [r#""\q""#, r#""\z""#, "0b2", "ok"] {
+        tally.record(&regularised_from_lexlucid(input, Edition::E2021));
+    }
+    assert_eq!(tally.reasons.get("unknown escape"), Some(&2));
+    assert_eq!(tally.reasons.values().sum::<usize>(), 3);
+    assert_eq!(
+        tally.categories.get(&RejectionCategory::UnknownEscape),
+        Some(&2)
+    );
+    assert!(!tally.categories.contains_key(&RejectionCategory::BareCr));
+}
+
+#[test]
+fn formatted_identifiers_show_the_form_before_normalisation() {
+    let Analysis::Accepts(_, tokens) =
+        lexlucid::analyse("e\u{301} r#e\u{301} \u{e9}", Edition::E2021)
+    else {
+        panic!("should be accepted");
+    };
+    let formatted: Vec<_> = tokens.iter().map(format_token).collect();
+    assert_eq!(
+        formatted,
+        [
+            "Identifier { represented_identifier: «‹E9›», original_identifier: Some(«e‹0301›») }, «e‹0301›»",
+            "Whitespace, « »",
+            "RawIdentifier { represented_identifier: «‹E9›», original_identifier: Some(«e‹0301›») }, «r#e‹0301›»",
+            "Whitespace, « »",
+            "Identifier { represented_identifier: «‹E9›», original_identifier: None }, «‹E9›»",
+        ]
+    );
+}
@@ -1,6 +1,8 @@
 //! Strings for testing lexing.
 #![cfg_attr(rustfmt, rustfmt_skip)]
 
+use std::path::{Path, PathBuf};
+
 /// The list of test strings used with the --short CLI option.
 pub const SHORTLIST: &[&str] = [
     "/* my source file */ fn main() { println!(\"zebra\"); }\n",
@@ -21,6 +23,29 @@ pub const LONGLIST: &[&str] = [
     " \n\r\t ",
     "\u{000B} \u{000C} \u{0085} \u{200E} \u{200F} \u{2028} \u{2029}",
 
+    // Each Pattern_White_Space character which isn't ASCII, or is an unusual ASCII control
+    "a\u{000B}b",
+    "a\u{000C}b",
+    "a\u{0085}b",
+    "a\u{200E}b",
+    "a\u{200F}b",
+    "a\u{2028}b",
+    "a\u{2029}b",
+
+    // White_Space characters which aren't Pattern_White_Space
+    "a\u{00A0}b",
+    "a\u{1680}b",
+    "a\u{2000}b",
+    "a\u{200A}b",
+    "a\u{202F}b",
+    "a\u{205F}b",
+    "a\u{3000}b",
+
+    // Other characters which some might expect to be whitespace
+    "a\u{180E}b",
+    "a\u{200B}b",
+    "a\u{001C}b",
+
 
     //// Comments
 
@@ -40,6 +65,16 @@ pub const LONGLIST: &[&str] = [
     "///",
     "//!",
     "//!!",
+    "///x\nfoo",
+    "a///x",
+    "a///x\nb",
+    "a //x\n///y\nb",
+    "///x\n///y",
+    "/**x*/foo",
+    "/**x*/.",
+    "a/**x*/b",
+    "a /**x*/ b",
+    "a/*x*//**y*/b",
 
     "/* comment */",
     "/* comment */+",
@@ -58,6 +93,16 @@ pub const LONGLIST: &[&str] = [
     "/***/+",
     "/*!*/",
     "/*!!*/",
+    "/**/",
+    "/***/",
+    "/****/",
+    "/**x*/",
+    "/** */",
+    "/*! */",
+    "/*!*/+",
+    "/***x*/",
+    "/**!*/",
+    "/*!**/",
     "/*/*/+",
     "/*/**/*/+",
     "/* xxx /*/+",
@@ -73,6 +118,18 @@ pub const LONGLIST: &[&str] = [
     "/* CR \r in block comment */",
     "/** CR \r in block doc comment */",
     "/*! CR \r in inner block doc comment */",
+    "//a\rb",
+    "///a\rb",
+    "//!a\rb",
+    "////a\rb",
+    "//a\r",
+    "///\r",
+    "//!\r",
+
+    "// tab \t form feed \x0c vertical tab \x0b in line comment",
+    "/// tab \t form feed \x0c vertical tab \x0b in line doc comment",
+    "//! tab \t form feed \x0c vertical tab \x0b in inner line doc comment",
+    "/** tab \t form feed \x0c vertical tab \x0b in block doc comment */",
 
     " //comment",
     " /* comment */ ",
@@ -109,6 +166,18 @@ pub const LONGLIST: &[&str] = [
     "..=",
     "...=",
     "..==",
+    "a..b",
+    "a..=b",
+    "a...b",
+    "a.. b",
+    "a ..b",
+    "0..1",
+    "0..=1",
+    "0...1",
+    "1.0..2",
+    "x..",
+    "x..=",
+    ".. =",
 
     "<<<<",
     "<=<=",
@@ -151,6 +220,32 @@ pub const LONGLIST: &[&str] = [
     "####",
     "#####",
 
+    // Guarded string forms (reserved in Rust 2024)
+    r##"#"x"#"##,
+    r##"#"x""##,
+    r###"##"x"##"###,
+    r##"# "x"#"##,
+    r##"#"x" #"##,
+    r##"#r"x"#"##,
+    r##"x#"y""##,
+    r#"#"x""#,
+    r#"#"""#,
+    "#foo",
+    "##foo",
+    "# #",
+    "#'a'",
+    r###"#r#"x"#"###,
+    // The most hashes a raw literal may have is 255
+    "r###############################################################################################################################################################################################################################################################\"x\"###############################################################################################################################################################################################################################################################",
+    "r################################################################################################################################################################################################################################################################\"x\"################################################################################################################################################################################################################################################################",
+    "br###############################################################################################################################################################################################################################################################\"x\"###############################################################################################################################################################################################################################################################",
+    "br################################################################################################################################################################################################################################################################\"x\"################################################################################################################################################################################################################################################################",
+    "cr###############################################################################################################################################################################################################################################################\"x\"###############################################################################################################################################################################################################################################################",
+    "cr################################################################################################################################################################################################################################################################\"x\"################################################################################################################################################################################################################################################################",
+    "r###############################################################################################################################################################################################################################################################\"x\"################################################################################################################################################################################################################################################################",
+    "r################################################################################################################################################################################################################################################################\"x\"###############################################################################################################################################################################################################################################################",
+    "r################################################################################################################################################################################################################################################################",
+
     "x ➖ y",
 
 
@@ -183,6 +278,9 @@ pub const LONGLIST: &[&str] = [
     "r#self",
     "r#super",
     "r#Self",
+    "r#Crate",
+    "r#SELF",
+    "r#self_",
 
     "🦁",
     "a🦁",
@@ -194,9 +292,29 @@ pub const LONGLIST: &[&str] = [
     "q\u{e1}",
     "r#qa\u{0301}",
     "r#q\u{e1}",
+    "\u{212b}ngstr\u{f6}m \u{c5}ngstr\u{f6}m",
+    "r#\u{212b}",
+
+    // Confusable and mixed-script identifiers
+    "a \u{0430}",
+    "p\u{0430}ypal paypal",
+    "\u{03bf}\u{043e}o",
     "Kelvin Kelvin",
     "Ⅹ Ⅰ Ⅽ",
 
+    // Identifiers with combining characters
+    "a\u{0301}\u{0302}",
+    "a\u{0301}\u{0302}\u{0303}+b",
+    "e\u{0323}\u{0301} e\u{0301}\u{0323}",
+    "r#e\u{0301}\u{0323}",
+    "x\u{0301}.y\u{0301}",
+    "\u{0301}",
+    "\u{0301}a",
+    "x \u{0301}",
+    "r#\u{0301}",
+    "r#self\u{0301}",
+    "r#_\u{0301}",
+
     // Example of a new character in Unicode 16.0
     "\u{105C9}",
     // Decomposed form of U+105C9
@@ -309,6 +427,9 @@ pub const LONGLIST: &[&str] = [
     r#" c""#,
     r#" c"yyy"#,
     r#" c"yyy""#,
+    "c\"x\"",
+    "cr\"x\"",
+    "cr#\"x\"#",
     "c\"x\ny\"",
     "c\"x\n\ny\"",
 
@@ -330,6 +451,10 @@ pub const LONGLIST: &[&str] = [
     r#" r"\" "#,
     r#" r"\\" "#,
     r#" r"\u{00E6}" "#,
+    " r\"tab \t form feed \x0c vertical tab \x0b\" ",
+    " br\"tab \t form feed \x0c vertical tab \x0b\" ",
+    " cr\"tab \t form feed \x0c vertical tab \x0b\" ",
+    " r#\"\t\x0c\x0b\"# ",
 
     r##" r#""# "##,
     r##" r#"""# "##,
@@ -351,6 +476,10 @@ pub const LONGLIST: &[&str] = [
     r###" r#"extra"##n "###,
     r####" r#"extra"### "####,
     r####" r##"extra"### "####,
+    r####" r###"short"## "####,
+    r####" br###"short"## "####,
+    r####" cr###"short"# "####,
+    r###" r##"a"b"#c "###,
 
     r##" r""suff "##,
     r##" r"xxx"suff "##,
@@ -416,6 +545,29 @@ pub const LONGLIST: &[&str] = [
     " '£#x",
     " 'r#x'y",
     " 'r#xx'y",
+    "'r#foo",
+    "'r#foo#",
+    "'foo#",
+    "'foo#bar",
+
+    // Character literals and lifetimes which share a prefix
+    "'a'",
+    "'a",
+    "'a'b",
+    "'ab'",
+    "'_'",
+    "'_",
+    "'_'_",
+    "'漢'",
+    "'漢",
+    "'漢'x",
+    "'漢字'",
+    "'a: loop { break 'a; }",
+    "'a:'b",
+    "'a''b",
+    "['a']",
+    "['a]",
+
     " x'",
     " x''",
     " x'''",
@@ -498,6 +650,8 @@ pub const LONGLIST: &[&str] = [
     "'r#self",
     "'r#super",
     "'r#Self",
+    "'r#Crate",
+    "'r#self_",
 
     //// Forms related to the "Guarded string literals" 2024 reservations
     " #\"xxx\"# ",
@@ -625,6 +779,16 @@ pub const LONGLIST: &[&str] = [
     r#" "\u{00E6" "#,
     r#" "\u{00E6abc" "#,
     r#" "\u{00E6\n" "#,
+    r#" "\u" "#,
+    r#" "\uABCD" "#,
+    r#" "\uABCD}" "#,
+    r#" "\u{" "#,
+    r#" "\u 41" "#,
+    r#" '\u' "#,
+    r#" '\u41' "#,
+    r#" '\u{41' "#,
+    r#" c"\u" "#,
+    r#" c"\u41}" "#,
     r#" "\u00E6}" "#,
     r#" "\u00E6" "#,
     r#" "\u{00}" "#,
@@ -634,13 +798,36 @@ pub const LONGLIST: &[&str] = [
     r#" "\u{10FFFF}" "#,
     r#" "\u{110000}" "#,
     r#" "\u{00G6}" "#,
+    r#" "\u{D7FF}" "#,
     r#" "\u{D800}" "#,
+    r#" "\u{DBFF}" "#,
+    r#" "\u{DC00}" "#,
+    r#" "\u{DFFF}" "#,
+    r#" "\u{E000}" "#,
+    r#" "\u{FFFFFF}" "#,
+    r#" '\u{DFFF}' "#,
+    r#" '\u{110000}' "#,
+    r#" c"\u{D800}" "#,
+    r#" c"\u{110000}" "#,
     r#" "\u{-1}" "#,
     r#" "\u{}" "#,
     r#" "\u{_}" "#,
     r#" "\u{_00e6}" "#,
     r#" "\u{00e6_}" "#,
     r#" "\u00e6" "#,
+    r#" "\u{1_F600}" "#,
+    r#" "\u{1_0_F_F_F_F}" "#,
+    r#" "\u{1_0_F_F_F_F_}" "#,
+    r#" "\u{1__F600__}" "#,
+    r#" "\u{1_1_0_0_0_0}" "#,
+    r#" "\u{1_0_0_0_0_0_0}" "#,
+    r#" "\u{_1}" "#,
+    r#" "\u{__}" "#,
+    r#" "\u{_}_" "#,
+    r#" '\u{_1}' "#,
+    r#" '\u{1_F600}' "#,
+    r#" c"\u{_1}" "#,
+    r#" c"\u{1_F600}" "#,
 
     r#" b"\x00 \x7f \' \" \n \r \t \\ \0" "#,
     r#" b"\x80 \xff" "#,
@@ -680,16 +867,22 @@ pub const LONGLIST: &[&str] = [
     " \"string\\\ncontinuation\"",
     " \"string\\\n continuation\"",
     " \"string\\\n\n\r\tcontinuation\"",
+    " \"string\\\n\n\ncontinuation\"",
+    " \"string\\\n\n\n\"",
+    " \"one\\\n  two\\\n\n  three\"",
+    " \"\\\n\\\n\\\n\"",
     " \"string\\\n    ",
 
     " b\"string\\\ncontinuation\"",
     " b\"string\\\n continuation\"",
     " b\"string\\\n\n\r\tcontinuation\"",
+    " b\"string\\\n\n\ncontinuation\"",
     " b\"string\\\n    ",
 
     " c\"string\\\ncontinuation\"",
     " c\"string\\\n continuation\"",
     " c\"string\\\n\n\r\tcontinuation\"",
+    " c\"string\\\n\n\ncontinuation\"",
     " c\"string\\\n    ",
 
     " \"non-NFC a\u{0301}\" ",
@@ -728,6 +921,19 @@ pub const LONGLIST: &[&str] = [
     r#" c"\u{00000}" "#,
     r#" c"\u{0_0_0_0}" "#,
     " cr\"\0\" ",
+    r#" c"\u{00}a" "#,
+
+    //// NUL in other string families
+
+    r#" "\0" "#,
+    r#" "\00" "#,
+    r#" b"\0" "#,
+    r#" b"\x00" "#,
+    r#" r"\0" "#,
+    r#" br"\0" "#,
+    r#" cr"\0" "#,
+    " b'\0' ",
+    " '\0' ",
 
 
     //// Integer
@@ -860,6 +1066,25 @@ pub const LONGLIST: &[&str] = [
     "123e4em",
     "123.4E_5",
     "123.4E",
+    "1.0e_",
+    "1.0E_",
+    "1.0E_x",
+    "1.0e_f32",
+    "1e_",
+    "1E_x",
+    "1e_9",
+    "1E_9",
+    "1e__9",
+    "1.0e",
+    "1.0f32",
+    "1.0e3f64",
+    "1.0_e",
+    "1.0e+",
+    "1.0E-_",
+    "1e3e",
+    "1.0ef32",
+    "1.0_f32",
+    "1.0e3_f64",
     "123EM",
     "123.4EM",
     "123E4EM",
@@ -877,6 +1102,18 @@ pub const LONGLIST: &[&str] = [
     "0b1. 0",
     "0o1. 2",
     "0x1. 2",
+    "0x1.0",
+    "0o1.0",
+    "0x1.",
+    "0b1.",
+    "0o7.0f32",
+    "0b1.0e5",
+    "0x1.2.3",
+    "0x1p4",
+    "0x1.foo",
+    "0b1.foo",
+    "0o1.e3",
+    "0x1..",
     "0x1e2",
     "0xAe2",
     "0o1e2",
@@ -899,6 +1136,14 @@ pub const LONGLIST: &[&str] = [
 
     "\u{feff}bom",
     "bom\u{feff}\n\u{feff}bom\n",
+    "\u{feff}fn main() {}",
+    "\u{feff}\u{feff}bom",
+    "\u{feff} bom",
+    "\u{feff}\r\nbom",
+    "\u{feff}#!/bin/sh\nbom",
+    "bom\u{feff}",
+    "b\u{feff}om",
+    "\"\u{feff}\"",
 
 
     //// CRLF removal
@@ -908,6 +1153,35 @@ pub const LONGLIST: &[&str] = [
     " \"one\r\ntwo\rthree\" ",
     " //!one\r\ntwo\r\nthree ",
     " //!one\r\ntwo\rthree\" ",
+    " r\"one\r\ntwo\r\nthree\" ",
+    " r\"one\r\ntwo\rthree\" ",
+    " r#\"one\r\ntwo\r\nthree\"# ",
+    " r#\"one\r\ntwo\rthree\"# ",
+    " br\"one\r\ntwo\r\nthree\" ",
+    " br\"one\r\ntwo\rthree\" ",
+    " cr\"one\r\ntwo\r\nthree\" ",
+    " cr\"one\r\ntwo\rthree\" ",
+    " r\"\r\r\n\" ",
+    " r\"\r\n\r\" ",
+    // Mixing CRLF and lone CR in and out of literals
+    "a\rb\r\nc",
+    "a\r\r\nb",
+    "a\r\n\rb",
+    "\r\n\"x\"\r",
+    "\r\"x\"\r\n",
+    "\r\n\"x\r\ny\"\r",
+    "\r\"x\r\ny\"\r\n",
+    "\r\n\"x\ry\"\r\n",
+    "x\r\n// comment\r\ny",
+    "x\r// comment\ry",
+    "x\r\n/// doc\r\ny",
+    "x\r\n/* comment\r */\r\ny",
+    "x\r\n/** doc\r\n */\r\ny",
+    "'\r'",
+    "'\r\n'",
+    "\"\\\r\n  x\"",
+    "\"\\\r  x\"",
+    "b\"\r\n\" c\"\r\n\" \"\r\n\"",
 
 
     //// Shebang
@@ -920,9 +1194,46 @@ pub const LONGLIST: &[&str] = [
     "#![attr]\nfn",
     "#! [attr]\nfn",
     "#!\n[attr]\nfn",
-    // Unhandled cases of shebang (comment-in-attribute)
+    "#!/bin/sh\n",
+    "#!/bin/sh\nfn",
+    "#![feature]",
+    "#![feature(x)]\nfn",
+    "#!\t\n [attr]\nfn",
+    "#!\t/x\nfn",
+    "#!/x\r\nfn",
+    "#!/x\r\n",
+    "#!/x\rfn",
+    "#!\r\nfn",
+    " #!/x",
+    " #!/x\nfn",
+    "\n#!/x\nfn",
+    // Comments between the `#!` and the `[`
     "#! /* oops */ [attr]\nfn",
     "#! // oops\n[attr]\nfn",
+    "#!/* one */ /* /* two */ */\n// three\n[attr]",
+    "#! /* unterminated [attr]",
+    // Doc-comments aren't skipped
+    "#! /// doc\n[attr]\nfn",
+    "#! /** doc */ [attr]\nfn",
+    "#! //! doc\n[attr]\nfn",
+    // rustc's lookahead ignores an error in a later token
+    "#! [attr] 'unterminated",
+    "#! 'unterminated [attr]",
+
+    //// Frontmatter
+
+    // rustc (at the version we compare against) doesn't support frontmatter, so `compare` sees these
+    // as punctuation. Use `inspect --cleaning=shebang-and-frontmatter` to see them removed.
+    "---\n[package]\nedition = \"2024\"\n---\nfn main() {}",
+    "---cargo\n---\nfn",
+    "--- cargo \n---  \nfn",
+    "#!/usr/bin/env cargo\n---\n---\nfn",
+    "\n  \n---\n---\nfn",
+    "----\n---\n----\nfn",
+    "---\nunterminated",
+    "---\n----\nfn",
+    "--- not an infostring\n---\nfn",
+    "fn ---\n---",
 
     //// Delimiters
 
@@ -933,3 +1244,43 @@ pub const LONGLIST: &[&str] = [
 ]
 .as_slice();
 
+
+/// Reads inputs from the `.rs` files in a directory (and its subdirectories).
+///
+/// Each file is one input. Returns each file's path and contents, in order of path.
+///
+/// Files longer than `max_input_length` bytes, and files which aren't valid UTF-8, are skipped
+/// (with a note on standard error).
+pub fn read_corpus_dir(
+    dir: &Path,
+    max_input_length: usize,
+) -> std::io::Result<Vec<(PathBuf, String)>> {
+    let mut paths = Vec::new();
+    find_rs_files(dir, &mut paths)?;
+    paths.sort();
+    let mut inputs = Vec::new();
+    for path in paths {
+        let bytes = std::fs::read(&path)?;
+        if bytes.len() > max_input_length {
+            eprintln!("skipping {}: {} bytes long", path.display(), bytes.len());
+            continue;
+        }
+        match String::from_utf8(bytes) {
+            Ok(input) => inputs.push((path, input)),
+            Err(_) => eprintln!("skipping {}: not UTF-8", path.display()),
+        }
+    }
+    Ok(inputs)
+}
+
+fn find_rs_files(dir: &Path, paths: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_rs_files(&path, paths)?;
+        } else if path.extension().is_some_and(|extension| extension == "rs") {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
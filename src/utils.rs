@@ -17,3 +17,41 @@ pub fn escape_for_display(input: &str) -> String {
     }
     s
 }
+
+/// Formats the character represented by a character literal, as `char::escape_debug` shows it, in
+/// single quotes.
+///
+/// For example BEL is shown as `'\u{7}'`.
+#[cfg(feature = "rustc-harness")]
+pub fn format_represented_char(c: char) -> String {
+    format!("'{}'", c.escape_debug())
+}
+
+/// Formats the string represented by a string literal, as `str::escape_debug` shows it, in double
+/// quotes.
+#[cfg(feature = "rustc-harness")]
+pub fn format_represented_string(s: &str) -> String {
+    format!("\"{}\"", s.escape_debug())
+}
+
+/// Returns a Rust string literal representing the input, suitable for pasting into source code.
+///
+/// Escapes the same characters as [`escape_for_display`] (as well as `"` and `\`), using `\n`,
+/// `\r` and `\t` where possible and `\u{...}` otherwise.
+#[cfg(feature = "rustc-harness")]
+pub fn rust_string_literal(input: &str) -> String {
+    let mut s = String::from('"');
+    for c in input.chars() {
+        match c {
+            '"' => s.push_str("\\\""),
+            '\\' => s.push_str("\\\\"),
+            '\n' => s.push_str("\\n"),
+            '\r' => s.push_str("\\r"),
+            '\t' => s.push_str("\\t"),
+            c if c.is_ascii_graphic() || c == ' ' => s.push(c),
+            c => s.push_str(&format!("\\u{{{:04X}}}", c as u32)),
+        }
+    }
+    s.push('"');
+    s
+}